@@ -1,5 +1,6 @@
 use std::ops::AddAssign;
 
+use dashu::base::BitTest;
 use dashu::integer::rand::UniformIBig;
 use rand::{
     distributions::uniform::{SampleUniform, UniformSampler},
@@ -18,6 +19,12 @@ pub struct RichEntropy {
     ///
     /// Don't quote me on the soundness of this calculation
     pub entropy_bits: f32,
+    /// The worst-case (min-entropy) number of bits, i.e. `-log2(max_i p_i)`
+    ///
+    /// Only differs from `entropy_bits` when selection is non-uniform, e.g. a word list with
+    /// non-uniform [`RichWord::weight`]s (see [`crate::generate_words`]). This is the figure that
+    /// matters for guessing resistance.
+    pub min_entropy_bits: f32,
     /// The exponent of the log10 of the number of variations
     ///
     /// This is useful for displaying the number of variations in scientific notation
@@ -47,28 +54,77 @@ impl RichEntropy {
     }
 
     fn calculate_impl(variations: BigInteger) -> Self {
-        // TODO: I don't quite trust the results of the log2 calculation
-        // TODO: The calculations seem to get stuck for big inputs (e.g. 1000 words)
+        let entropy_bits_f64 = log2_exact(variations);
+        let entropy_bits_f64 = if entropy_bits_f64.is_finite() {
+            entropy_bits_f64
+        } else {
+            0.0
+        };
+
+        let log10_variations = entropy_bits_f64 * std::f64::consts::LOG10_2;
+        let variations_exponent = log10_variations.floor();
+        let variations_mantissa = 10f64.powf(log10_variations - variations_exponent);
 
-        let variations = dashu::Decimal::from(variations);
-        let log2 = variations.ln() / dashu::Decimal::from(2).ln();
+        let entropy_bits = entropy_bits_f64 as f32;
 
-        let log10 = (variations.ln() / dashu::Decimal::from(10).ln())
-            .floor()
-            .to_int()
-            .value();
-        let mantissa = variations / dashu::Decimal::from(10).powi(log10.clone());
+        RichEntropy {
+            entropy_bits,
+            // selection is uniform here, so the worst case equals the average case
+            min_entropy_bits: entropy_bits,
+            variations_exponent: variations_exponent as u32,
+            variations_mantissa: variations_mantissa as f32,
+        }
+    }
 
+    /// Like [`RichEntropy::calculate`], but for a non-uniform selection (e.g. a weighted word
+    /// list, see [`crate::generate_words`]) where the worst-case entropy differs from the average
+    /// case.
+    ///
+    /// `min_entropy_bits` should be the sum of `-log2(max_i p_i)` across all sampled positions.
+    #[cfg(target_arch = "wasm32")]
+    pub fn calculate_weighted(variations: js_sys::BigInt, min_entropy_bits: f32) -> Self {
+        let variations =
+            BigInteger::from_str_radix(&variations.to_string(10).unwrap().as_string().unwrap(), 10)
+                .unwrap();
         RichEntropy {
-            // TODO: investigate a panic that occurs on revision 522fe52
-            //       with wortliste_522fe52.txt, default settings and 4, 5 or 6 words (not on 3 or 7)
-            entropy_bits: log2.to_f64().value() as f32,
-            variations_exponent: log10.to_f32().value() as u32,
-            variations_mantissa: mantissa.to_f32().value(),
+            min_entropy_bits,
+            ..Self::calculate_impl(variations)
+        }
+    }
+
+    /// Like [`RichEntropy::calculate`], but for a non-uniform selection (e.g. a weighted word
+    /// list, see [`crate::generate_words`]) where the worst-case entropy differs from the average
+    /// case.
+    ///
+    /// `min_entropy_bits` should be the sum of `-log2(max_i p_i)` across all sampled positions.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn calculate_weighted(variations: BigInteger, min_entropy_bits: f32) -> Self {
+        RichEntropy {
+            min_entropy_bits,
+            ..Self::calculate_impl(variations)
         }
     }
 }
 
+/// Computes `log2(x)` exactly for a `BigInteger`: `(bit_length - 1) + log2(x / 2^(bit_length -
+/// 1))`, taking the log2 of only the top ~53 significant bits so this stays `O(bits)` and
+/// constant time regardless of magnitude, instead of overflowing `f64` or panicking on huge
+/// inputs.
+///
+/// Returns `f64::NEG_INFINITY` for `x == 0`.
+pub(crate) fn log2_exact(x: BigInteger) -> f64 {
+    let bit_length = x.bit_len();
+    if bit_length == 0 {
+        return f64::NEG_INFINITY;
+    }
+    let shift = bit_length.saturating_sub(53);
+    let top_bits = dashu::Decimal::from(&x >> shift);
+    let log2_top_bits = (top_bits.ln() / dashu::Decimal::from(2).ln())
+        .to_f64()
+        .value();
+    shift as f64 + log2_top_bits
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Default, Clone)]
 pub struct IntegerWrapper(pub BigInteger);
 
@@ -110,3 +166,14 @@ impl UniformSampler for DashuUniformSampler {
         IntegerWrapper(self.0.sample(rng))
     }
 }
+
+impl DashuUniformSampler {
+    /// Like [`UniformSampler::sample`], but statically requires a cryptographically secure RNG.
+    ///
+    /// Prefer this over the unconstrained `sample` for real passphrase generation; the
+    /// unconstrained path remains available for reproducible test vectors where a
+    /// non-cryptographic, seedable generator is desirable.
+    pub fn sample_secure<R: Rng + rand::CryptoRng + ?Sized>(&self, rng: &mut R) -> IntegerWrapper {
+        UniformSampler::sample(self, rng)
+    }
+}
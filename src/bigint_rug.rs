@@ -4,7 +4,6 @@ use rand::{
     distributions::uniform::{SampleUniform, UniformSampler},
     Rng,
 };
-use rug::ops::CompleteRound;
 
 pub const BIGINT_LIB: &str = "rug";
 
@@ -16,6 +15,12 @@ pub struct RichEntropy {
     ///
     /// Don't quote me on the soundness of this calculation
     pub entropy_bits: f32,
+    /// The worst-case (min-entropy) number of bits, i.e. `-log2(max_i p_i)`
+    ///
+    /// Only differs from `entropy_bits` when selection is non-uniform, e.g. a word list with
+    /// non-uniform [`RichWord::weight`]s (see [`crate::generate_words`]). This is the figure that
+    /// matters for guessing resistance.
+    pub min_entropy_bits: f32,
     /// The exponent of the log10 of the number of variations
     ///
     /// This is useful for displaying the number of variations in scientific notation
@@ -28,42 +33,63 @@ pub struct RichEntropy {
     pub variations_mantissa: f32,
 }
 
-// Source: https://gitlab.com/tspiteri/rug/-/blob/cf96b2c811ccff258ec1483400c0fc8ceff973a6/src/integer/traits.rs#L335-344
-fn float_from_int(i: &BigInteger) -> rug::Float {
-    let abs = i.as_abs();
-    let mut prec = abs.significant_bits();
-    // avoid copying trailing zeros
-    if let Some(zeros) = abs.find_one(0) {
-        prec -= zeros;
-    }
-    prec = prec.max(rug::float::prec_min());
-    rug::Float::with_val(prec, i)
-}
-
 impl RichEntropy {
     pub fn calculate(variations: BigInteger) -> Self {
-        let variations = float_from_int(&variations);
-        let precision = variations.prec();
-
-        let log2 = variations.clone().log2().to_f32();
-        let variations_exponent = variations
-            .clone()
-            .log10()
-            .floor()
-            .to_u32_saturating()
-            .unwrap();
-        let variations_mantissa = (variations
-            / rug::Float::u_pow_u(10, variations_exponent).complete(precision))
-        .to_f32();
+        // selection is uniform here, so the worst case equals the average case
+        Self::calculate_impl(variations)
+    }
+
+    /// Like [`RichEntropy::calculate`], but for a non-uniform selection (e.g. a weighted word
+    /// list, see [`crate::generate_words`]) where the worst-case entropy differs from the average
+    /// case.
+    ///
+    /// `min_entropy_bits` should be the sum of `-log2(max_i p_i)` across all sampled positions.
+    pub fn calculate_weighted(variations: BigInteger, min_entropy_bits: f32) -> Self {
+        RichEntropy {
+            min_entropy_bits,
+            ..Self::calculate_impl(variations)
+        }
+    }
+
+    fn calculate_impl(variations: BigInteger) -> Self {
+        let entropy_bits_f64 = log2_exact(variations);
+        let entropy_bits_f64 = if entropy_bits_f64.is_finite() {
+            entropy_bits_f64
+        } else {
+            0.0
+        };
+
+        let log10_variations = entropy_bits_f64 * std::f64::consts::LOG10_2;
+        let variations_exponent = log10_variations.floor();
+        let variations_mantissa = 10f64.powf(log10_variations - variations_exponent);
+
+        let entropy_bits = entropy_bits_f64 as f32;
 
         RichEntropy {
-            entropy_bits: log2,
-            variations_exponent,
-            variations_mantissa,
+            entropy_bits,
+            min_entropy_bits: entropy_bits,
+            variations_exponent: variations_exponent as u32,
+            variations_mantissa: variations_mantissa as f32,
         }
     }
 }
 
+/// Computes `log2(x)` exactly for a `BigInteger`: `(bit_length - 1) + log2(x / 2^(bit_length -
+/// 1))`, taking the log2 of only the top ~53 significant bits so this stays `O(bits)` and
+/// constant time regardless of magnitude, instead of overflowing `f64` or panicking on huge
+/// inputs.
+///
+/// Returns `f64::NEG_INFINITY` for `x == 0`.
+pub(crate) fn log2_exact(x: BigInteger) -> f64 {
+    let bit_length = x.significant_bits();
+    if bit_length == 0 {
+        return f64::NEG_INFINITY;
+    }
+    let shift = bit_length.saturating_sub(53);
+    let top_bits: BigInteger = x >> shift;
+    shift as f64 + top_bits.to_f64().log2()
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Default, Clone)]
 pub struct IntegerWrapper(pub BigInteger);
 
@@ -122,3 +148,14 @@ impl UniformSampler for RugUniformSampler {
         IntegerWrapper(self.range.clone().random_below(&mut rng) + &self.low)
     }
 }
+
+impl RugUniformSampler {
+    /// Like [`UniformSampler::sample`], but statically requires a cryptographically secure RNG.
+    ///
+    /// Prefer this over the unconstrained `sample` for real passphrase generation; the
+    /// unconstrained path remains available for reproducible test vectors where a
+    /// non-cryptographic, seedable generator is desirable.
+    pub fn sample_secure<R: Rng + rand::CryptoRng + ?Sized>(&self, rng: &mut R) -> IntegerWrapper {
+        UniformSampler::sample(self, rng)
+    }
+}
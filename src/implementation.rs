@@ -1,27 +1,122 @@
 use std::{collections::HashMap, num::NonZeroUsize};
 
-use color_eyre::eyre::{bail, Result};
 use itertools::Itertools as _;
-use rand::{distributions::WeightedIndex, Rng, SeedableRng};
+use rand::{
+    distributions::WeightedIndex,
+    rngs::{adapter::ReseedingRng, OsRng},
+    CryptoRng, Rng, RngCore, SeedableRng,
+};
+use rand_chacha::ChaCha20Core;
 use unicode_normalization::UnicodeNormalization as _;
+use unicode_segmentation::UnicodeSegmentation as _;
 use wasm_bindgen::prelude::wasm_bindgen;
 
 use crate::bigint::{BigInteger, IntegerWrapper};
 
+/// Bytes generated between automatic reseeds of [`RngWrapper`]'s default generator from the OS.
+const DEFAULT_RESEED_INTERVAL_BYTES: u64 = 64 * 1024;
+
+/// Backing generator for [`RngWrapper`]: either the default, automatically reseeding generator,
+/// or a deterministic one built from a caller-supplied seed.
+enum RngSource {
+    Reseeding(ReseedingRng<ChaCha20Core, OsRng>),
+    Seeded(rand_chacha::ChaCha20Rng),
+}
+
+impl RngCore for RngSource {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            RngSource::Reseeding(rng) => rng.next_u32(),
+            RngSource::Seeded(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            RngSource::Reseeding(rng) => rng.next_u64(),
+            RngSource::Seeded(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            RngSource::Reseeding(rng) => rng.fill_bytes(dest),
+            RngSource::Seeded(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            RngSource::Reseeding(rng) => rng.try_fill_bytes(dest),
+            RngSource::Seeded(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+// Both variants are backed by the ChaCha20 stream cipher, so `RngSource` is cryptographically
+// secure regardless of which one is active; this lets it satisfy the `CryptoRng` bound that
+// `generate_words`/`generate_words_naive` require of their entry points.
+impl CryptoRng for RngSource {}
+
 /// A wrapper around the random number generator.
 ///
 /// This wrapper is necessary to construct the RNG from JavaScript.
 ///
-/// Uses a cryptographically secure random number generator.
+/// By default uses a ChaCha20 stream cipher core that is automatically reseeded from the OS
+/// entropy source after a configurable number of generated bytes, giving forward secrecy for
+/// long-running generation (e.g. a server or browser tab producing many passphrases) without
+/// having to pay for a fresh OS read on every draw. [`RngWrapper::from_seed`] and
+/// [`RngWrapper::from_seed_bytes`] build a deterministic generator instead, for testing, auditing,
+/// or "regenerate this exact passphrase" workflows.
 #[wasm_bindgen]
-pub struct RngWrapper(#[wasm_bindgen(skip)] pub rand::rngs::StdRng);
+pub struct RngWrapper(#[wasm_bindgen(skip)] RngSource);
 
 #[wasm_bindgen]
 impl RngWrapper {
-    /// Creates a new instance of the random number generator.
+    /// Creates a new instance of the random number generator, reseeding from the OS every
+    /// [`DEFAULT_RESEED_INTERVAL_BYTES`] bytes.
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
-        RngWrapper(rand::rngs::StdRng::from_entropy())
+        Self::with_reseed_interval(DEFAULT_RESEED_INTERVAL_BYTES)
+    }
+
+    /// Creates a new instance of the random number generator, reseeding from the OS every
+    /// `reseed_interval_bytes` bytes of output.
+    #[wasm_bindgen]
+    pub fn with_reseed_interval(reseed_interval_bytes: u64) -> Self {
+        let core = ChaCha20Core::from_entropy();
+        RngWrapper(RngSource::Reseeding(ReseedingRng::new(
+            core,
+            reseed_interval_bytes,
+            OsRng,
+        )))
+    }
+
+    /// Creates a deterministic generator seeded from `seed`, via [`SeedableRng::seed_from_u64`].
+    ///
+    /// Unlike the default reseeding generator, this one never reseeds from the OS, so the same
+    /// `seed` and call sequence always produce the same output.
+    #[wasm_bindgen]
+    pub fn from_seed(seed: u64) -> Self {
+        RngWrapper(RngSource::Seeded(rand_chacha::ChaCha20Rng::seed_from_u64(
+            seed,
+        )))
+    }
+
+    /// Like [`RngWrapper::from_seed`], but takes the full 32-byte seed directly instead of
+    /// expanding a `u64` through [`SeedableRng::seed_from_u64`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `seed` isn't exactly 32 bytes.
+    #[wasm_bindgen]
+    pub fn from_seed_bytes(seed: Vec<u8>) -> Result<RngWrapper, String> {
+        let seed: [u8; 32] = seed
+            .try_into()
+            .map_err(|_| "seed must be exactly 32 bytes".to_string())?;
+        Ok(RngWrapper(RngSource::Seeded(
+            rand_chacha::ChaCha20Rng::from_seed(seed),
+        )))
     }
 
     /// Generates a vector of random digits.
@@ -54,6 +149,20 @@ pub struct RichWord {
     pub word: String,
     #[serde(default)]
     pub meanings: Vec<String>,
+    /// An optional grammatical category ("noun", "adjective", "intensifier", ...) used by the
+    /// `cli` feature's `--template` mode to pick this word for a matching `{category}` slot.
+    #[serde(default)]
+    pub category: Option<String>,
+    /// An optional weight (e.g. corpus frequency) biasing generation toward this word.
+    ///
+    /// Treated as a multiplicity: a word with weight `w` is counted as if it appeared `w` times
+    /// in its length group, both in the reported variation count and in the odds of being picked.
+    /// Defaults to `1` when absent, so unweighted word lists behave exactly as before weights
+    /// existed. A weight of `0` excludes the word entirely, as if it weren't in the list at all
+    /// (see [`WordDb::build_database`]). Only [`generate_words`] applies weights to the odds;
+    /// [`generate_words_naive`] draws uniformly, so there a weight only matters if it is `0`.
+    #[serde(default)]
+    pub weight: Option<u32>,
 }
 
 #[wasm_bindgen]
@@ -70,7 +179,12 @@ impl RichWord {
     /// A new instance of [`RichWord`].
     #[wasm_bindgen(constructor)]
     pub fn new(word: String, meanings: Vec<String>) -> Self {
-        RichWord { word, meanings }
+        RichWord {
+            word,
+            meanings,
+            category: None,
+            weight: None,
+        }
     }
 }
 
@@ -83,6 +197,10 @@ pub struct PreprocessOptions {
     pub keep_case: bool,
     pub use_umlauts: bool,
     pub min_word_length: Option<usize>,
+    /// The unit `min_word_length` is measured in; should match whatever [`LengthUnit`] the word
+    /// list will later be built with via [`WordDb::build_database`], so filtering and generation
+    /// agree on what "length" means.
+    pub length_unit: LengthUnit,
     #[wasm_bindgen(skip)]
     pub exclude_regexes: Vec<regex::Regex>,
 }
@@ -96,12 +214,19 @@ impl PreprocessOptions {
     /// * `keep_case` - Controls whether words should be lower-cased.
     /// * `use_umlauts` - Controls whether words with umlauts are filtered out.
     /// * `min_word_length` - Controls whether words with insufficient length are removed.
+    /// * `length_unit` - The unit `min_word_length` is measured in.
     #[wasm_bindgen(constructor)]
-    pub fn new(keep_case: bool, use_umlauts: bool, min_word_length: Option<usize>) -> Self {
+    pub fn new(
+        keep_case: bool,
+        use_umlauts: bool,
+        min_word_length: Option<usize>,
+        length_unit: LengthUnit,
+    ) -> Self {
         PreprocessOptions {
             keep_case,
             use_umlauts,
             min_word_length,
+            length_unit,
             exclude_regexes: Vec::new(),
         }
     }
@@ -140,7 +265,7 @@ pub fn preprocess_word_list(words: Vec<RichWord>, options: &PreprocessOptions) -
         .into_iter()
         .filter(|word| {
             if let Some(min_word_length) = options.min_word_length {
-                word.word.len() >= min_word_length
+                options.length_unit.measure(&word.word) >= min_word_length
             } else {
                 true
             }
@@ -170,24 +295,133 @@ pub fn preprocess_word_list(words: Vec<RichWord>, options: &PreprocessOptions) -
         .collect()
 }
 
+/// The unit [`WordDb`] measures word and `max_length` lengths in.
+///
+/// Defaults to [`LengthUnit::Bytes`], matching word_otter's historical behavior. Combine with NFC
+/// normalization (already applied by [`WordDb::build_database`]) when using [`LengthUnit::Chars`]
+/// or [`LengthUnit::Graphemes`], since normalization keeps the scalar/grapheme count of a given
+/// word stable regardless of how its input encoding happened to compose accented characters.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthUnit {
+    /// UTF-8 byte length (`str::len`). Cheapest, but a word with umlauts or other multibyte
+    /// characters counts for more than its perceived length.
+    Bytes,
+    /// Count of Unicode scalar values (`str::chars().count()`).
+    Chars,
+    /// Count of Unicode extended grapheme clusters, i.e. user-perceived characters.
+    Graphemes,
+}
+
+impl Default for LengthUnit {
+    fn default() -> Self {
+        LengthUnit::Bytes
+    }
+}
+
+impl LengthUnit {
+    /// Measures `word`'s length in this unit.
+    pub fn measure(&self, word: &str) -> usize {
+        match self {
+            LengthUnit::Bytes => word.len(),
+            LengthUnit::Chars => word.chars().count(),
+            LengthUnit::Graphemes => word.graphemes(true).count(),
+        }
+    }
+}
+
+/// A packed, contiguous store for the words of a single [`WordDb`] length group.
+///
+/// Instead of a `Vec<String>` (one heap allocation per word), every word in the group is appended
+/// to a single `Vec<u8>`, back to back. Words in a group share the same length under the
+/// `WordDb`'s [`LengthUnit`], but since that metric need not equal UTF-8 byte length (e.g.
+/// `Chars` or `Graphemes` with multibyte characters), their byte lengths can still differ, so each
+/// word's end offset is recorded explicitly instead of assuming a fixed stride. This is the same
+/// packing idea cracken's `WordsBuf` uses to keep multi-million-word lists cheap to build and hold
+/// in memory.
+#[derive(Debug, Default)]
+struct WordsBuf {
+    bytes: Vec<u8>,
+    /// The exclusive end byte offset of each word within `bytes`; a word's start offset is the
+    /// previous entry's end offset, or `0` for the first word.
+    offsets: Vec<usize>,
+    /// Per-word weight, in the same order as the words packed into `bytes`. Defaults to `1` for
+    /// words whose [`RichWord::weight`] was absent.
+    weights: Vec<u64>,
+}
+
+impl WordsBuf {
+    fn new() -> Self {
+        WordsBuf::default()
+    }
+
+    /// Appends `word` to the buffer with the given `weight`.
+    fn push(&mut self, word: &str, weight: u64) {
+        self.bytes.extend_from_slice(word.as_bytes());
+        self.offsets.push(self.bytes.len());
+        self.weights.push(weight);
+    }
+
+    /// The number of words stored in this group.
+    fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// The weight of the word at `index`.
+    fn weight(&self, index: usize) -> u64 {
+        self.weights[index]
+    }
+
+    /// The sum of every word's weight in this group; `0` for an empty group.
+    fn total_weight(&self) -> u64 {
+        self.weights.iter().sum()
+    }
+
+    /// The byte range of the word at `index` within `bytes`.
+    fn byte_range(&self, index: usize) -> std::ops::Range<usize> {
+        let start = if index == 0 { 0 } else { self.offsets[index - 1] };
+        start..self.offsets[index]
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &str> {
+        (0..self.len()).map(|index| &self[index])
+    }
+}
+
+impl std::ops::Index<usize> for WordsBuf {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &str {
+        std::str::from_utf8(&self.bytes[self.byte_range(index)])
+            .expect("word bytes are valid UTF-8")
+    }
+}
+
+/// A normalized, deduplicated word list, grouped by [`LengthUnit`] for the DP algorithm in
+/// [`Algorithm`].
 #[derive(Debug)]
-struct WordDb {
-    word_groups: HashMap<NonZeroUsize, Vec<String>>,
+pub struct WordDb {
+    word_groups: HashMap<NonZeroUsize, WordsBuf>,
     min_length: NonZeroUsize,
+    max_length: NonZeroUsize,
     meanings: HashMap<String, Vec<String>>,
+    length_unit: LengthUnit,
 }
 
 impl WordDb {
     ///
-    /// Returns None if words is empty or only contains empty strings.
+    /// Returns None if words is empty, only contains empty strings, or every word has an explicit
+    /// [`RichWord::weight`] of `0` (which excludes it from the list, see that field's docs).
     ///
-    pub fn build_database(mut words: Vec<RichWord>) -> Option<Self> {
+    pub fn build_database(mut words: Vec<RichWord>, length_unit: LengthUnit) -> Option<Self> {
         // run unicode normalization on all words
         words = words
             .into_iter()
-            .map(|RichWord { word, meanings }| RichWord {
+            .map(|RichWord { word, meanings, weight, .. }| RichWord {
                 word: word.nfc().collect(),
                 meanings,
+                category: None,
+                weight,
             })
             .collect();
         // sort words alphabetically
@@ -198,6 +432,12 @@ impl WordDb {
             .coalesce(|mut a, b| {
                 if a.word == b.word {
                     a.meanings.extend(b.meanings);
+                    a.weight = match (a.weight, b.weight) {
+                        (None, None) => None,
+                        (Some(x), None) => Some(x),
+                        (None, Some(y)) => Some(y),
+                        (Some(x), Some(y)) => Some(x + y),
+                    };
                     Ok(a)
                 } else {
                     Err((a, b))
@@ -213,24 +453,37 @@ impl WordDb {
             words.remove(0);
         }
 
+        // a weight of 0 excludes the word entirely: counting it would let a length group's total
+        // weight collapse to 0, which makes generate_words' WeightedIndex::new panic instead of
+        // just skewing the odds
+        words.retain(|word| word.weight != Some(0));
+
         if words.is_empty() {
             return None;
         }
 
         let mut map = HashMap::new();
         let mut meanings = HashMap::new();
-        let mut min_length: NonZeroUsize = words[0].word.len().try_into().expect("no empty words");
+        let mut min_length: NonZeroUsize = length_unit
+            .measure(&words[0].word)
+            .try_into()
+            .expect("no empty words");
         let mut max_length: NonZeroUsize = min_length;
 
         for RichWord {
             word,
             meanings: word_meanings,
+            weight,
+            ..
         } in words
         {
-            let length = word.len().try_into().expect("no empty words");
+            let length: NonZeroUsize = length_unit
+                .measure(&word)
+                .try_into()
+                .expect("no empty words");
 
-            let group_vec = map.entry(length).or_insert(Vec::new());
-            group_vec.push(word.clone());
+            let group_buf = map.entry(length).or_insert_with(WordsBuf::new);
+            group_buf.push(&word, weight.map(u64::from).unwrap_or(1));
 
             meanings
                 .entry(word)
@@ -248,47 +501,130 @@ impl WordDb {
         for group_len in 1..max_length.get() {
             let group_len = NonZeroUsize::new(group_len).unwrap();
 
-            let _ignored = map.entry(group_len).or_insert(Vec::new());
+            let _ignored = map.entry(group_len).or_insert_with(WordsBuf::new);
         }
 
         Some(WordDb {
             word_groups: map,
             min_length,
+            max_length,
             meanings,
+            length_unit,
         })
     }
 
-    fn get_group(&self, len: NonZeroUsize) -> &Vec<String> {
+    /// The [`LengthUnit`] this database measures word and length-constraint lengths in.
+    pub fn length_unit(&self) -> LengthUnit {
+        self.length_unit
+    }
+
+    fn get_group(&self, len: NonZeroUsize) -> &WordsBuf {
         self.word_groups.get(&len).unwrap()
     }
 
     ///
-    /// n_len: Returns the number of words with the given length.
+    /// W_len: Returns the sum of every word's weight (see [`RichWord::weight`]) in the given
+    /// length group, i.e. the group's word count with weight-1 words.
     ///
-    fn group_size(&self, len: NonZeroUsize) -> usize {
-        if let Some(group_vec) = self.word_groups.get(&len) {
-            group_vec.len()
-        } else {
-            0
-        }
+    fn group_weight(&self, len: NonZeroUsize) -> u64 {
+        self.word_groups
+            .get(&len)
+            .map(|group_buf| group_buf.total_weight())
+            .unwrap_or(0)
+    }
+
+    /// Returns `word`'s weight, or `0` if it isn't present in its length group.
+    fn word_weight(&self, word: &str) -> u64 {
+        let Some(len) = NonZeroUsize::new(self.length_unit.measure(word)) else {
+            return 0;
+        };
+        let Some(buf) = self.word_groups.get(&len) else {
+            return 0;
+        };
+        buf.iter()
+            .position(|candidate| candidate == word)
+            .map(|index| buf.weight(index))
+            .unwrap_or(0)
     }
 
-    fn shortest_group_len(&self) -> NonZeroUsize {
+    pub fn shortest_group_len(&self) -> NonZeroUsize {
         self.min_length
     }
 
-    fn attach_meanings(&self, words: &[String]) -> Vec<RichWord> {
+    /// The length (in this database's [`LengthUnit`]) of its longest word.
+    pub fn longest_group_len(&self) -> NonZeroUsize {
+        self.max_length
+    }
+
+    /// Iterates over every word across all length groups, in no particular order across groups
+    /// (words within a group stay in their original insertion order).
+    pub fn all_words(&self) -> impl Iterator<Item = &str> {
+        self.word_groups.values().flat_map(|buf| buf.iter())
+    }
+
+    /// Returns whether `word` is present in this database, at its exact case and normalization
+    /// (see [`WordDb::build_database`]).
+    pub fn contains_word(&self, word: &str) -> bool {
+        let Some(len) = NonZeroUsize::new(self.length_unit.measure(word)) else {
+            return false;
+        };
+        self.word_groups
+            .get(&len)
+            .is_some_and(|buf| buf.iter().any(|candidate| candidate == word))
+    }
+
+    pub fn attach_meanings(&self, words: &[String]) -> Vec<RichWord> {
         words
             .iter()
             .map(|word| RichWord {
                 word: word.clone(),
                 meanings: self.meanings.get(word).cloned().unwrap_or_default(),
+                category: None,
+                weight: None,
             })
             .collect()
     }
 }
 
-struct Algorithm {
+/// Errors from the core generation functions ([`generate_words`], [`generate_words_naive`],
+/// [`generate_words_from_index`]).
+///
+/// Kept separate from `color_eyre` so the core library doesn't need to depend on it; the `cli`
+/// feature's binary converts these with `?` like any other `std::error::Error`.
+#[derive(Debug)]
+pub enum GenerationError {
+    /// `max_length` is too small to fit `words` words, even using the shortest available word.
+    LengthConstraintsUnsatisfiable,
+    /// No candidate words remain, e.g. every word is longer than `max_length / words`.
+    NoCandidateWords,
+    /// The given index is not in `[0, N)`, where `N` is the reported variation count.
+    IndexOutOfRange,
+    /// `distinct` was requested but fewer candidate words remain than `words` requested.
+    NotEnoughDistinctWords,
+}
+
+impl std::fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenerationError::LengthConstraintsUnsatisfiable => {
+                write!(f, "Length constraints cannot be fulfilled")
+            }
+            GenerationError::NoCandidateWords => {
+                write!(f, "Input file contained no valid words")
+            }
+            GenerationError::IndexOutOfRange => {
+                write!(f, "Index is out of range for the given word count and max length")
+            }
+            GenerationError::NotEnoughDistinctWords => {
+                write!(f, "Not enough distinct candidate words to fill the requested word count")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerationError {}
+
+pub struct Algorithm {
     word_db: WordDb,
     memoize_variations_for_length: HashMap<u32, BigInteger>,
     memoize_unreachable_variations_at_depth: HashMap<(u32, u32), BigInteger>,
@@ -296,7 +632,7 @@ struct Algorithm {
 
 #[allow(non_snake_case)]
 impl Algorithm {
-    fn new(word_db: WordDb) -> Self {
+    pub fn new(word_db: WordDb) -> Self {
         Algorithm {
             word_db,
             memoize_variations_for_length: Default::default(),
@@ -304,6 +640,11 @@ impl Algorithm {
         }
     }
 
+    /// Gives back the [`WordDb`] this algorithm was constructed with.
+    pub fn word_db(&self) -> &WordDb {
+        &self.word_db
+    }
+
     fn variations_for_length(&mut self, max_length: u32) -> &BigInteger {
         fn variations_for_length_impl(
             word_db: &WordDb,
@@ -316,7 +657,7 @@ impl Algorithm {
                 let mut sum = BigInteger::ZERO;
 
                 for group_len in 1..=max_length {
-                    let n_k = word_db.group_size(
+                    let w_k = word_db.group_weight(
                         NonZeroUsize::new(group_len.try_into().expect("iterator over range 1.."))
                             .expect("iterator over range 1.."),
                     );
@@ -325,7 +666,7 @@ impl Algorithm {
                         .get(&(max_length - group_len))
                         .expect("must have been calculated before");
 
-                    sum += n_k * f_x_minus_k;
+                    sum += w_k * f_x_minus_k;
                 }
 
                 sum
@@ -338,7 +679,8 @@ impl Algorithm {
             // begin calculating values from the bottom up
             for max_length_ in 0..=max_length {
                 if !memoization.contains_key(&(max_length_)) {
-                    let value = variations_for_length_impl(&self.word_db, memoization, max_length_);
+                    let value =
+                        variations_for_length_impl(&self.word_db, memoization, max_length_);
                     memoization.insert(max_length_, value);
                 }
             }
@@ -367,7 +709,7 @@ impl Algorithm {
                 let mut sum = BigInteger::ZERO;
 
                 for group_len in 1..=max_length {
-                    let n_k = word_db.group_size(
+                    let w_k = word_db.group_weight(
                         NonZeroUsize::new(group_len.try_into().expect("iterator over range 1.."))
                             .expect("iterator over range 1.."),
                     );
@@ -376,7 +718,7 @@ impl Algorithm {
                         .get(&(max_length - (group_len - 1), depth - 1))
                         .expect("must have been calculated before");
 
-                    sum += n_k * g_x_minus_k_minus_one_D_minus_one;
+                    sum += w_k * g_x_minus_k_minus_one_D_minus_one;
                 }
 
                 sum
@@ -414,13 +756,136 @@ impl Algorithm {
     ///
     /// Returns the number of possible variations chaining this number of `words` up to a `max_length`.
     ///
-    fn variations_for_length_and_depth(&mut self, max_length: u32, depth: u32) -> BigInteger {
+    pub fn variations_for_length_and_depth(&mut self, max_length: u32, depth: u32) -> BigInteger {
         let f_x = self.variations_for_length(max_length).clone();
         let g_x_minus_D_D =
             self.unreachable_variations_at_depth(max_length.saturating_sub(depth), depth);
 
         f_x - g_x_minus_D_D
     }
+
+    ///
+    /// Inverse of the sampling done in [`generate_words`]: turns an `index` in
+    /// `[0, variations_for_length_and_depth(max_length, words))` into the unique word sequence it
+    /// corresponds to.
+    ///
+    /// Walks the same per-step loop as [`generate_words`], but instead of sampling from a
+    /// `WeightedIndex`, consumes `index` digit by digit: at each step the cumulative block size of
+    /// every `group_len` is subtracted from `index` until the remainder selects a group, then the
+    /// remainder is divided by that group's sub-count to get a weighted position, which is
+    /// resolved to a concrete word by walking the group's weights.
+    ///
+    /// Callers must ensure `index < variations_for_length_and_depth(max_length, words)`.
+    pub fn unrank(
+        &mut self,
+        mut max_length: u32,
+        mut words: u32,
+        mut index: BigInteger,
+    ) -> Vec<String> {
+        let mut out = Vec::with_capacity(words as usize);
+
+        while words > 0 {
+            let step_max_len = max_length - (words - 1);
+
+            let mut group_len = 1;
+            let mut sub = BigInteger::ZERO;
+            for candidate_len in 1..=step_max_len {
+                let w_k = self.word_db.group_weight(
+                    NonZeroUsize::new(candidate_len.try_into().unwrap())
+                        .expect("iterator over range 1.."),
+                );
+                let candidate_sub =
+                    self.variations_for_length_and_depth(step_max_len - candidate_len, words - 1);
+                let block = BigInteger::from(w_k) * &candidate_sub;
+
+                if index < block {
+                    group_len = candidate_len;
+                    sub = candidate_sub;
+                    break;
+                }
+                index -= block;
+            }
+
+            let group = self
+                .word_db
+                .get_group(NonZeroUsize::new(group_len.try_into().unwrap()).unwrap());
+
+            // weighted_pos < W_k by construction, so it always fits in a u64
+            let weighted_pos: u64 = (index.clone() / sub.clone())
+                .to_string()
+                .parse()
+                .expect("weighted_pos fits in the group's total weight");
+            index %= &sub;
+
+            let mut word_pos = 0;
+            let mut cumulative_weight = 0u64;
+            for candidate_pos in 0..group.len() {
+                cumulative_weight += group.weight(candidate_pos);
+                if weighted_pos < cumulative_weight {
+                    word_pos = candidate_pos;
+                    break;
+                }
+            }
+
+            let word = group[word_pos].to_owned();
+            max_length -= u32::try_from(self.word_db.length_unit.measure(&word)).unwrap();
+            words -= 1;
+            out.push(word);
+        }
+
+        out
+    }
+
+    ///
+    /// Inverse of [`Algorithm::unrank`]: turns a word sequence back into the smallest index in
+    /// `[0, variations_for_length_and_depth(max_length, words))` that `unrank` maps to it.
+    ///
+    /// With weight-1 words that index is unique; a word of weight `w` occupies `w` consecutive
+    /// weighted positions in its group, so several indices unrank to the same sequence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any word in `chosen_words` is not present in the loaded word list.
+    pub fn rank(&mut self, mut max_length: u32, chosen_words: &[String]) -> BigInteger {
+        let mut words = u32::try_from(chosen_words.len()).unwrap();
+        let mut index = BigInteger::ZERO;
+
+        for word in chosen_words {
+            let step_max_len = max_length - (words - 1);
+            let group_len = u32::try_from(self.word_db.length_unit.measure(word)).unwrap();
+
+            // every shorter group length sorts before this word's group
+            for candidate_len in 1..group_len {
+                let w_k = self.word_db.group_weight(
+                    NonZeroUsize::new(candidate_len.try_into().unwrap())
+                        .expect("iterator over range 1.."),
+                );
+                let candidate_sub =
+                    self.variations_for_length_and_depth(step_max_len - candidate_len, words - 1);
+                index += w_k * &candidate_sub;
+            }
+
+            let group = self
+                .word_db
+                .get_group(NonZeroUsize::new(group_len.try_into().unwrap()).unwrap());
+            let word_pos = group
+                .iter()
+                .position(|candidate| candidate == word)
+                .expect("word must be present in its length group");
+
+            // every word sorting before `word` within the group occupies a weighted block of its
+            // own ahead of it
+            let cumulative_weight: u64 = (0..word_pos).map(|pos| group.weight(pos)).sum();
+
+            let sub = self.variations_for_length_and_depth(step_max_len - group_len, words - 1);
+            index += cumulative_weight * &sub;
+
+            max_length -= group_len;
+            words -= 1;
+        }
+
+        index
+    }
 }
 
 /// The result returned on successful generation of words.
@@ -428,7 +893,7 @@ impl Algorithm {
 /// It contains the generated words and a number indicating how many variations were possible
 /// with the given input parameters.
 ///
-/// This struct is returned by the [`generate_words`] and [`generate_words_naive`] functions.
+/// This struct is returned by the wasm-exposed `generate_words` and `generate_words_naive`.
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen(getter_with_clone)]
 pub struct GenerationResult {
@@ -449,212 +914,507 @@ impl GenerationResult {
     }
 }
 
-/// Generates a sequence of words based on the provided input while respecting a maximum total length.
-///
-/// The function uses a random number generator (`rng`) to create a sequence
-/// of a number of `word_count` words from the `input_words` list.
+/// Generates a sequence of `words` words, not exceeding `max_length` combined (measured in
+/// `word_db`'s [`LengthUnit`]), using `rng` to both pick a word length at each step (weighted by
+/// how many weighted completions it leaves reachable, so that every final sequence is drawn with
+/// probability proportional to the product of its words' [`RichWord::weight`]s, i.e. uniformly
+/// for an unweighted list) and a word within that length group.
 ///
-/// The generated words will not exceed the specified `max_length`.
-/// 
-/// Use the [`generate_words_naive`] function if no length constraints are needed.
+/// If `distinct` is set, no word is chosen twice in a row: the previous step's word is excluded
+/// (with its group's weight adjusted to match) before sampling the next one. Unlike
+/// [`generate_words_naive`]'s full sampling-without-replacement, this is a weaker "no two adjacent
+/// words identical" guarantee, since the exact DP model here assumes every step's word pool is
+/// independent of earlier choices; the reported `variations` is therefore a (very slight)
+/// overestimate when `distinct` is set, as it doesn't account for the adjacent exclusion.
 ///
-/// # Arguments
-///
-/// * `rng` - A mutable reference to the random number generator.
-/// * `input_words` - A vector of [`RichWord`]s to choose from.
-/// * `word_count` - The number of words to generate.
-/// * `max_length` - The maximum combined length of the generated words in bytes.
-///
-/// # Returns
+/// Use [`generate_words_naive`] if no length constraint is needed.
 ///
-/// A result containing either the generated words and the number of variations,
-/// or an error message if the generation fails.
-/// 
-/// See [`GenerationResult`] for more information about the returned values.
+/// Returns the exact worst-case (min-)entropy of the word selection alongside the words and
+/// `variations`, i.e. `-log2(max_i p_i)` summed over every weighted draw made along the way. This
+/// only differs from the uniform `log2(variations)` figure [`crate::bigint::RichEntropy::calculate`]
+/// would report when [`RichWord::weight`] biases the word list, and should be passed to
+/// [`crate::bigint::RichEntropy::calculate_weighted`] instead.
 ///
 /// # Errors
 ///
-/// Returns an error if no input words are given (empty list or empty strings) or
-/// if the length constraints cannot be fulfilled.
-#[cfg(any(target_arch = "wasm32", doc))]
-#[wasm_bindgen]
-pub fn generate_words(
-    rng: &mut RngWrapper,
-    input_words: Vec<RichWord>,
-    word_count: usize,
-    max_length: usize,
-) -> Result<GenerationResult, String> {
-    let (words, variations) =
-        generate_words_impl(rng, input_words, word_count, max_length).map_err(|err| err.to_string())?;
-    Ok(GenerationResult::new(words, variations))
-}
-
-#[cfg(not(any(target_arch = "wasm32", doc)))]
-pub fn generate_words(
-    rng: &mut RngWrapper,
-    input_words: Vec<RichWord>,
-    word_count: usize,
-    max_length: usize,
-) -> Result<(Vec<RichWord>, BigInteger)> {
-    generate_words_impl(rng, input_words, word_count, max_length)
-}
-
+/// Returns [`GenerationError::LengthConstraintsUnsatisfiable`] if `max_length` is too small to fit
+/// `words` words, even using the shortest available word.
 #[allow(non_snake_case)]
-fn generate_words_impl(
-    rng: &mut RngWrapper,
-    input_words: Vec<RichWord>,
-    word_count: usize,
+pub fn generate_words(
+    rng: &mut (impl Rng + CryptoRng),
+    word_db: WordDb,
+    words: usize,
     max_length: usize,
-) -> Result<(Vec<RichWord>, BigInteger)> {
-    let word_db = match WordDb::build_database(input_words) {
-        None => bail!("Input file contained no valid words"),
-        Some(word_db) => word_db,
-    };
-
-    if word_count * word_db.shortest_group_len().get() > max_length {
-        bail!("Length constraints cannot be fulfilled");
+    distinct: bool,
+) -> Result<(Vec<RichWord>, BigInteger, f64), GenerationError> {
+    if words * word_db.shortest_group_len().get() > max_length {
+        return Err(GenerationError::LengthConstraintsUnsatisfiable);
     }
 
-    let mut generated_words: Vec<String> = Vec::with_capacity(word_count);
+    let mut generated_words: Vec<String> = Vec::with_capacity(words);
     let mut algorithm = Algorithm::new(word_db);
 
     // TODO unwrap
     let mut max_length = u32::try_from(max_length).unwrap();
-    let mut words = u32::try_from(word_count).unwrap();
+    let mut words = u32::try_from(words).unwrap();
 
     // already calculates and memoizes all values used in the following loop
     let variations = algorithm.variations_for_length_and_depth(max_length, words);
 
+    let mut previous_word: Option<String> = None;
+    let mut min_entropy_bits = 0.0_f64;
+
     while words > 0 {
         let step_max_len: u32 = max_length - (words - 1);
 
-        let distr_iter = (1..=step_max_len).map(|group_len| {
-            let n_k = algorithm.word_db.group_size(
-                NonZeroUsize::new(group_len.try_into().unwrap()).expect("iterator over range 1.."),
-            );
-            let f_dash_x_minus_k_D_minus_one =
-                algorithm.variations_for_length_and_depth(step_max_len - group_len, words - 1);
+        let group_weights: Vec<IntegerWrapper> = (1..=step_max_len)
+            .map(|group_len| {
+                let mut w_k = algorithm.word_db.group_weight(
+                    NonZeroUsize::new(group_len.try_into().unwrap())
+                        .expect("iterator over range 1.."),
+                );
+                if let Some(previous) = previous_word
+                    .as_deref()
+                    .filter(|_| distinct)
+                    .filter(|word| algorithm.word_db.length_unit.measure(word) as u32 == group_len)
+                {
+                    w_k -= algorithm.word_db.word_weight(previous);
+                }
+                let f_dash_x_minus_k_D_minus_one =
+                    algorithm.variations_for_length_and_depth(step_max_len - group_len, words - 1);
 
-            IntegerWrapper(n_k * f_dash_x_minus_k_D_minus_one)
-        });
-        let distribution = WeightedIndex::new(distr_iter).unwrap();
+                IntegerWrapper(w_k * f_dash_x_minus_k_D_minus_one)
+            })
+            .collect();
 
-        let group_len = 1 + rng.0.sample(&distribution);
+        // The actual sum of this step's weights (not `variations_for_length_and_depth`'s
+        // memoized, distinct-unaware figure), so `min_entropy_bits` matches the distribution
+        // `WeightedIndex` below actually samples from.
+        let mut step_total = IntegerWrapper(BigInteger::ZERO);
+        for w in &group_weights {
+            step_total += w;
+        }
+        let max_group_weight = group_weights
+            .iter()
+            .max()
+            .expect("step_max_len >= 1, so at least one group_len was considered")
+            .0
+            .clone();
+        min_entropy_bits += crate::bigint::log2_exact(step_total.0)
+            - crate::bigint::log2_exact(max_group_weight);
+
+        // `group_weights`/`word_weights` (below) are rebuilt from scratch every step, since
+        // `distinct` exclusion and the shrinking length budget change which weights are even in
+        // play from one word to the next; an O(1) alias-method table amortizes its setup cost
+        // across repeated draws from the *same* fixed distribution, which doesn't apply here, so
+        // rand's ready-made, O(log n)-per-sample `WeightedIndex` is used instead. This supersedes
+        // the alias-method `WeightedSampler` originally added for frequency weighting: that
+        // per-step rebuild cost made it no better than `WeightedIndex` in practice, so it was
+        // removed rather than wired in here.
+        let distribution = WeightedIndex::new(group_weights).unwrap();
+
+        let group_len = 1 + rng.sample(&distribution);
         let group = algorithm
             .word_db
             .get_group(NonZeroUsize::new(group_len).unwrap());
-        let index = rng.0.gen_range(0..group.len());
-        let word = group[index].clone();
 
-        max_length -= u32::try_from(word.len()).unwrap();
+        let excluded_index = previous_word
+            .as_deref()
+            .filter(|_| distinct)
+            .and_then(|word| group.iter().position(|candidate| candidate == word));
+
+        let word_weights: Vec<u64> = (0..group.len())
+            .map(|i| {
+                if Some(i) == excluded_index {
+                    0
+                } else {
+                    group.weight(i)
+                }
+            })
+            .collect();
+
+        let total_word_weight: u64 = word_weights.iter().sum();
+        let max_word_weight = *word_weights
+            .iter()
+            .max()
+            .expect("group has at least one word");
+        min_entropy_bits +=
+            (total_word_weight as f64).log2() - (max_word_weight as f64).log2();
+
+        let word_distribution = WeightedIndex::new(word_weights).unwrap();
+        let index = rng.sample(&word_distribution);
+        let word = group[index].to_owned();
+
+        max_length -= u32::try_from(algorithm.word_db.length_unit.measure(&word)).unwrap();
         words -= 1;
+        if distinct {
+            previous_word = Some(word.clone());
+        }
         generated_words.push(word);
     }
 
     Ok((
         algorithm.word_db.attach_meanings(&generated_words),
         variations,
+        min_entropy_bits,
     ))
 }
 
-/// Generates a sequence of words based on the provided input without a length constraint.
-/// 
-/// Refer to [`generate_words`] for more information about the arguments and return values.
-/// 
+/// Like [`generate_words`], but limits word length by simply discarding words longer than
+/// `max_length / words` instead of running the exact DP algorithm.
+///
+/// Only affects results when `max_length` is `Some`. Instead of considering all words that
+/// together reach the max length, this simple algorithm only considers words with a length
+/// `<= max_length / words`, to make sure the generated password does not get longer than
+/// `max_length`.
+///
+/// If `distinct` is set, words are sampled without replacement, so the same word never appears
+/// twice; `variations` becomes the falling factorial `len * (len - 1) * ... * (len - words + 1)`
+/// instead of `len ^ words`.
+///
 /// # Errors
-/// 
-/// Returns an error if no input words are given (empty list or empty strings).
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
+///
+/// Returns [`GenerationError::NoCandidateWords`] if no word satisfies the length constraint, or
+/// [`GenerationError::NotEnoughDistinctWords`] if `distinct` is set and fewer candidate words
+/// remain than `words` requested.
 pub fn generate_words_naive(
+    rng: &mut (impl Rng + CryptoRng),
+    word_db: WordDb,
+    words: usize,
+    max_length: Option<usize>,
+    distinct: bool,
+) -> Result<(Vec<RichWord>, BigInteger), GenerationError> {
+    let max_word_length = max_length.map(|len| len / words);
+    let length_unit = word_db.length_unit();
+
+    let mut candidates: Vec<&str> = word_db
+        .all_words()
+        .filter(|word| {
+            max_word_length.map_or(true, |max_len| length_unit.measure(word) <= max_len)
+        })
+        .collect();
+
+    if candidates.is_empty() {
+        return Err(GenerationError::NoCandidateWords);
+    }
+    if distinct && words > candidates.len() {
+        return Err(GenerationError::NotEnoughDistinctWords);
+    }
+
+    let mut chosen_words = Vec::with_capacity(words);
+    let mut variations = BigInteger::from(1);
+
+    for _ in 0..words {
+        let word_index = rng.gen_range(0..candidates.len());
+        chosen_words.push(candidates[word_index].to_owned());
+        variations *= candidates.len();
+        if distinct {
+            candidates.swap_remove(word_index);
+        }
+    }
+
+    Ok((word_db.attach_meanings(&chosen_words), variations))
+}
+
+/// Wasm entry point mirroring [`generate_words`], taking a raw word list and the wasm-friendly
+/// [`RngWrapper`] instead of a pre-built [`WordDb`] and a generic `Rng`.
+///
+/// # Errors
+///
+/// Returns an error if `input_words` is empty or only contains empty strings, or if the length
+/// constraints cannot be fulfilled.
+#[cfg(any(target_arch = "wasm32", doc))]
+#[wasm_bindgen(js_name = "generate_words")]
+pub fn generate_words_wasm(
     rng: &mut RngWrapper,
     input_words: Vec<RichWord>,
     word_count: usize,
+    max_length: usize,
+    distinct: bool,
+    length_unit: LengthUnit,
 ) -> Result<GenerationResult, String> {
-    let (words, variations) = generate_words_naive_impl(rng, input_words, word_count, None)
-        .map_err(|err| err.to_string())?;
+    let word_db = WordDb::build_database(input_words, length_unit)
+        .ok_or_else(|| GenerationError::NoCandidateWords.to_string())?;
+    let (words, variations, _min_entropy_bits) =
+        generate_words(&mut rng.0, word_db, word_count, max_length, distinct)
+            .map_err(|err| err.to_string())?;
     Ok(GenerationResult::new(words, variations))
 }
 
-/// Generates a sequence of words based on the provided input with an optional length constraint.
-/// 
-/// Refer to [`generate_words`] for more information about the arguments and return values.
-/// 
-/// When providing a `max_length`, the generated words will not exceed this length. The algorithm
-/// used is simpler and does not achieve as many possible variations as [`generate_words`].
-/// 
+/// Wasm entry point mirroring [`generate_words_naive`]; see [`generate_words_wasm`] for why the
+/// signature differs from the native, generic [`generate_words_naive`].
+///
 /// # Errors
-/// 
-/// Returns an error if no **suitable** input words are given (empty list, empty strings **or**
-/// unachievable length constraints).
-#[cfg(not(target_arch = "wasm32"))]
-pub fn generate_words_naive(
+///
+/// Returns an error if `input_words` is empty or only contains empty strings.
+#[cfg(any(target_arch = "wasm32", doc))]
+#[wasm_bindgen(js_name = "generate_words_naive")]
+pub fn generate_words_naive_wasm(
     rng: &mut RngWrapper,
     input_words: Vec<RichWord>,
     word_count: usize,
-    max_length: Option<usize>,
-) -> Result<(Vec<RichWord>, BigInteger)> {
-    generate_words_naive_impl(rng, input_words, word_count, max_length)
+    distinct: bool,
+    length_unit: LengthUnit,
+) -> Result<GenerationResult, String> {
+    let word_db = WordDb::build_database(input_words, length_unit)
+        .ok_or_else(|| GenerationError::NoCandidateWords.to_string())?;
+    let (words, variations) = generate_words_naive(&mut rng.0, word_db, word_count, None, distinct)
+        .map_err(|err| err.to_string())?;
+    Ok(GenerationResult::new(words, variations))
 }
 
-fn generate_words_naive_impl(
-    rng: &mut RngWrapper,
-    mut input_words: Vec<RichWord>,
+/// Deterministically regenerates the same passphrase for identical `(seed, wordlist, settings)`
+/// inputs.
+///
+/// Internally seeds a [`rand_chacha::ChaCha20Rng`] from the given 32-byte seed and feeds it to
+/// the same generation path as [`generate_words`]. Useful for "brain wallet"-style recovery from
+/// a short secret, and for deterministic test fixtures.
+///
+/// # Errors
+///
+/// Returns an error if `input_words` is empty or only contains empty strings, or if the length
+/// constraints cannot be fulfilled.
+#[cfg(not(any(target_arch = "wasm32", doc)))]
+pub fn generate_words_from_seed(
+    seed: [u8; 32],
+    input_words: Vec<RichWord>,
+    word_count: usize,
+    max_length: usize,
+    distinct: bool,
+    length_unit: LengthUnit,
+) -> Result<(Vec<RichWord>, BigInteger, f64), GenerationError> {
+    let word_db =
+        WordDb::build_database(input_words, length_unit).ok_or(GenerationError::NoCandidateWords)?;
+    let mut rng = rand_chacha::ChaCha20Rng::from_seed(seed);
+    generate_words(&mut rng, word_db, word_count, max_length, distinct)
+}
+
+/// Like [`generate_words_from_seed`], but takes the seed as a hex or base64 string, so the web
+/// UI can offer deterministic regeneration without shipping the raw integer index.
+///
+/// # Errors
+///
+/// Returns an error if `seed` is not a valid hex or base64 encoding of exactly 32 bytes, if
+/// `input_words` is empty or only contains empty strings, or if the length constraints cannot be
+/// fulfilled.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn generate_words_from_seed(
+    seed: &str,
+    input_words: Vec<RichWord>,
     word_count: usize,
+    max_length: usize,
+    distinct: bool,
+    length_unit: LengthUnit,
+) -> Result<GenerationResult, String> {
+    let seed = decode_seed(seed)?;
+    let word_db = WordDb::build_database(input_words, length_unit)
+        .ok_or_else(|| GenerationError::NoCandidateWords.to_string())?;
+    let mut rng = rand_chacha::ChaCha20Rng::from_seed(seed);
+    let (words, variations, _min_entropy_bits) =
+        generate_words(&mut rng, word_db, word_count, max_length, distinct)
+            .map_err(|err| err.to_string())?;
+    Ok(GenerationResult::new(words, variations))
+}
+
+/// Decodes a 32-byte seed given as either a hex or base64 string.
+#[cfg(target_arch = "wasm32")]
+fn decode_seed(seed: &str) -> Result<[u8; 32], String> {
+    use base64::Engine as _;
+
+    let bytes = hex::decode(seed)
+        .or_else(|_| base64::engine::general_purpose::STANDARD.decode(seed))
+        .map_err(|_| "seed must be a hex or base64 encoded string".to_string())?;
+
+    bytes
+        .try_into()
+        .map_err(|_| "seed must decode to exactly 32 bytes".to_string())
+}
+
+/// Generates the exact passphrase at `index` in `[0, N)`, where `N =
+/// variations_for_length_and_depth(max_length, words)` is the same count [`generate_words`]
+/// reports as its `variations`.
+///
+/// This is [`generate_words`]'s deterministic counterpart, analogous to mapping an entropy
+/// integer to a mnemonic: given the same `word_db`, `words` and `max_length`, the same `index`
+/// always produces the same passphrase, giving a stable passphrase↔number mapping for storage or
+/// transport. The mapping is one-to-one only when every word has weight 1: a word of
+/// [`RichWord::weight`] `w` is counted `w` times in `N`, so `w` indices lead to it at that position.
+/// Use [`index_of_words`] to invert this; it returns the smallest such index.
+///
+/// # Errors
+///
+/// Returns [`GenerationError::LengthConstraintsUnsatisfiable`] if `max_length` is too small to fit
+/// `words` words, or [`GenerationError::IndexOutOfRange`] if `index` is not in `[0, N)`.
+pub fn generate_words_from_index(
+    word_db: WordDb,
+    words: usize,
+    max_length: usize,
+    index: BigInteger,
+) -> Result<(Vec<RichWord>, BigInteger), GenerationError> {
+    if words * word_db.shortest_group_len().get() > max_length {
+        return Err(GenerationError::LengthConstraintsUnsatisfiable);
+    }
+
+    let mut algorithm = Algorithm::new(word_db);
+    let max_length = u32::try_from(max_length).unwrap();
+    let words = u32::try_from(words).unwrap();
+
+    let variations = algorithm.variations_for_length_and_depth(max_length, words);
+    if index >= variations {
+        return Err(GenerationError::IndexOutOfRange);
+    }
+
+    let generated_words = algorithm.unrank(max_length, words, index);
+
+    Ok((
+        algorithm.word_db().attach_meanings(&generated_words),
+        variations,
+    ))
+}
+
+/// Inverse of [`generate_words_from_index`]: recovers the smallest `index` a sequence of
+/// `chosen_words` corresponds to, given the same `word_db` and `max_length` used to generate it.
+///
+/// # Panics
+///
+/// Panics if any word in `chosen_words` is not present in `word_db` (see [`Algorithm::rank`]).
+pub fn index_of_words(word_db: WordDb, max_length: usize, chosen_words: &[String]) -> BigInteger {
+    let mut algorithm = Algorithm::new(word_db);
+    let max_length = u32::try_from(max_length).unwrap();
+    algorithm.rank(max_length, chosen_words)
+}
+
+/// How words are joined into the final passphrase by [`PassphraseBuilder`].
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Separator {
+    /// A random digit `0`-`9` between each pair of words, whose 10 possibilities per separator
+    /// are folded into the reported [`GeneratedPassphrase::variations`].
+    #[default]
+    RandomDigit,
+    /// A fixed character between each pair of words, contributing no variations of its own.
+    Fixed(char),
+}
+
+/// The result of [`PassphraseBuilder::generate`].
+pub struct GeneratedPassphrase {
+    /// The fully assembled passphrase, words and separators together.
+    pub password: String,
+    /// The words the passphrase is made of, with dictionary meanings attached where available.
+    pub words: Vec<RichWord>,
+    /// The exact number of passphrases this configuration could have produced; combined with
+    /// [`crate::bigint::RichEntropy::calculate`] this gives the honest entropy of `password`.
+    pub variations: BigInteger,
+    /// The worst-case (min-)entropy of `password` in bits, i.e. `-log2(max_i p_i)` summed over
+    /// every draw made while generating it.
+    ///
+    /// Equal to `log2(variations)` unless the word list has non-uniform [`RichWord::weight`]s, in
+    /// which case it's the figure to pass to [`crate::bigint::RichEntropy::calculate_weighted`]
+    /// alongside `variations` for an honest report.
+    pub min_entropy_bits: f64,
+}
+
+/// Builder for the core passphrase-generation algorithm, decoupled from the CLI and from wasm.
+///
+/// Construct with [`PassphraseBuilder::new`], configure with the other builder methods, then call
+/// [`PassphraseBuilder::generate`] with a pre-built [`WordDb`] and an `Rng`. Word-list filtering
+/// (umlauts, minimum length, exclusion patterns, case) is a property of the `WordDb` you build —
+/// see [`preprocess_word_list`] — not of this builder.
+pub struct PassphraseBuilder {
+    words: usize,
     max_length: Option<usize>,
-) -> Result<(Vec<RichWord>, BigInteger)> {
-    let max_word_length = max_length.map(|len| len / word_count);
+    naive: bool,
+    distinct: bool,
+    separator: Separator,
+}
 
-    // run unicode normalization on all words and filter max length
-    input_words = input_words
-        .into_iter()
-        .filter(|word| {
-            if let Some(max_len) = max_word_length {
-                word.word.len() <= max_len
-            } else {
-                true
-            }
-        })
-        .map(|RichWord { word, meanings }| RichWord {
-            word: word.nfc().collect(),
-            meanings,
-        })
-        .collect();
-    // sort words alphabetically
-    input_words.sort_unstable_by(|a, b| a.word.cmp(&b.word));
-    // merge duplicates
-    input_words = input_words
-        .into_iter()
-        .coalesce(|mut a, b| {
-            if a.word == b.word {
-                a.meanings.extend(b.meanings);
-                Ok(a)
-            } else {
-                Err((a, b))
-            }
-        })
-        .collect();
-    // remove 0-length strings
-    if input_words
-        .first()
-        .map(|word| word.word.is_empty())
-        .unwrap_or(false)
-    {
-        input_words.remove(0);
+impl PassphraseBuilder {
+    /// Starts a builder for a passphrase made of this many words.
+    pub fn new(words: usize) -> Self {
+        PassphraseBuilder {
+            words,
+            max_length: None,
+            naive: false,
+            distinct: false,
+            separator: Separator::default(),
+        }
     }
 
-    if input_words.is_empty() {
-        bail!("Input file contained no valid words");
+    /// Limits the resulting passphrase (words only, not separators) to this many units, measured
+    /// in the `word_db` passed to [`PassphraseBuilder::generate`]'s [`WordDb::length_unit`].
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
     }
 
-    let mut out_words = Vec::with_capacity(word_count);
-    let mut variations = BigInteger::from(1);
+    /// Uses [`generate_words_naive`] instead of the exact DP [`generate_words`]; see
+    /// [`generate_words_naive`]'s docs for when that's desirable.
+    pub fn naive(mut self, naive: bool) -> Self {
+        self.naive = naive;
+        self
+    }
+
+    /// Forbids repeating a word: with `naive` set, words are drawn without replacement; otherwise
+    /// (whether or not `max_length` is set) the weaker "no two adjacent words identical" guarantee
+    /// is used instead — see [`generate_words`]'s docs.
+    pub fn distinct(mut self, distinct: bool) -> Self {
+        self.distinct = distinct;
+        self
+    }
 
-    for _ in 0..word_count {
-        let word_index = rng.0.gen_range(0..input_words.len());
-        out_words.push(input_words[word_index].clone());
-        variations *= input_words.len();
+    /// Sets how words are joined together. Defaults to [`Separator::RandomDigit`].
+    pub fn separator(mut self, separator: Separator) -> Self {
+        self.separator = separator;
+        self
     }
 
-    Ok((out_words, variations))
+    /// Generates a passphrase from `word_db` using `rng`.
+    pub fn generate(
+        &self,
+        rng: &mut (impl Rng + CryptoRng),
+        word_db: WordDb,
+    ) -> Result<GeneratedPassphrase, GenerationError> {
+        let (words, mut variations, mut min_entropy_bits) = if self.naive {
+            let (words, variations) =
+                generate_words_naive(rng, word_db, self.words, self.max_length, self.distinct)?;
+            // naive mode ignores RichWord::weight, so selection is uniform and the worst case
+            // equals the average case
+            let min_entropy_bits = crate::bigint::log2_exact(variations.clone());
+            (words, variations, min_entropy_bits)
+        } else {
+            // Without an explicit `max_length` there's no real length budget to enforce, but
+            // `generate_words` still needs *some* upper bound for its DP; default to the longest
+            // word's length times `self.words`, a bound no valid sequence can ever exceed, so
+            // `RichWord::weight` still biases selection instead of silently falling back to
+            // `generate_words_naive`'s uniform draw.
+            let max_length = self
+                .max_length
+                .unwrap_or_else(|| word_db.longest_group_len().get() * self.words);
+            generate_words(rng, word_db, self.words, max_length, self.distinct)?
+        };
+
+        let mut password = String::new();
+        for (i, word) in words.iter().enumerate() {
+            password.push_str(&word.word);
+
+            if i != words.len() - 1 {
+                match self.separator {
+                    Separator::Fixed(c) => password.push(c),
+                    Separator::RandomDigit => {
+                        let digit = rng.gen_range(0..=9);
+                        password.push(char::from_digit(digit, 10).expect("digit is 0..=9"));
+                        variations *= 10;
+                        min_entropy_bits += 10.0_f64.log2();
+                    }
+                }
+            }
+        }
+
+        Ok(GeneratedPassphrase {
+            password,
+            words,
+            min_entropy_bits,
+            variations,
+        })
+    }
 }
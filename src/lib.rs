@@ -1,15 +1,23 @@
+//! Exact-entropy, wordlist-based passphrase generation.
+//!
+//! [`WordDb`], [`Algorithm`], [`generate_words`], [`generate_words_naive`] and the
+//! [`PassphraseBuilder`] built on top of them have no dependency on `clap` or `color_eyre`, so
+//! other Rust programs can embed word_otter's exact-entropy passphrase generation directly
+//! instead of shelling out to the `word_otter` binary, which lives in `main.rs` behind the `cli`
+//! feature.
+
 #[cfg(all(
     any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"),
     not(feature = "dashu")
 ))]
 #[path = "bigint_rug.rs"]
-mod bigint;
+pub mod bigint;
 #[cfg(not(all(
     any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"),
     not(feature = "dashu")
 )))]
 #[path = "bigint_dashu.rs"]
-mod bigint;
+pub mod bigint;
 
 mod implementation;
 
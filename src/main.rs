@@ -1,40 +1,60 @@
 use std::{
-    collections::HashMap,
     fs::File,
     io::{BufRead, BufReader},
-    num::NonZeroUsize,
     path::PathBuf,
 };
 
-use bigint::{BigInteger, IntegerWrapper};
 use clap::Parser;
 use color_eyre::{
     eyre::{bail, eyre, Context},
     Result,
 };
-use itertools::Itertools as _;
-use rand::{distributions::WeightedIndex, Rng, SeedableRng};
+use rand::{Rng, SeedableRng};
 use regex::RegexBuilder;
-use unicode_normalization::UnicodeNormalization;
-
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"),
-    not(feature = "dashu")
-))]
-#[path = "bigint_rug.rs"]
-mod bigint;
-#[cfg(not(all(
-    any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"),
-    not(feature = "dashu")
-)))]
-#[path = "bigint_dashu.rs"]
-mod bigint;
+use unicode_normalization::UnicodeNormalization as _;
+use word_otter::{
+    bigint::{self, BigInteger},
+    Algorithm, GeneratedPassphrase, PassphraseBuilder, RichWord, Separator, WordDb,
+};
+
+/// The unit `--max-length` and `--min-word-length` are measured in.
+///
+/// Mirrors [`word_otter::LengthUnit`], kept as a separate type so the core library doesn't need a
+/// `clap` dependency (see `lib.rs`'s doc comment).
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum LengthUnit {
+    /// UTF-8 byte length. Cheapest, but a word with umlauts or other multibyte characters counts
+    /// for more than its perceived length.
+    Bytes,
+    /// Count of Unicode scalar values.
+    Chars,
+    /// Count of Unicode extended grapheme clusters, i.e. user-perceived characters.
+    Graphemes,
+}
+
+impl From<LengthUnit> for word_otter::LengthUnit {
+    fn from(unit: LengthUnit) -> Self {
+        match unit {
+            LengthUnit::Bytes => word_otter::LengthUnit::Bytes,
+            LengthUnit::Chars => word_otter::LengthUnit::Chars,
+            LengthUnit::Graphemes => word_otter::LengthUnit::Graphemes,
+        }
+    }
+}
 
 #[derive(Debug, clap::Parser)]
 struct Args {
-    /// Limit the resulting password to this length in bytes
+    /// Limit the resulting password to this length, measured in `--length-unit` units
     #[arg(long, short = 'L')]
     max_length: Option<usize>,
+    /// Unit `--max-length` and `--min-word-length` are measured in
+    ///
+    /// `chars` counts Unicode scalar values and `graphemes` counts user-perceived characters
+    /// (extended grapheme clusters); both avoid `bytes`' surprise that `--use-umlauts` words can
+    /// count for more than one unit per letter. Combined with NFC normalization, which word_otter
+    /// always applies, so grapheme counting is stable regardless of input encoding.
+    #[arg(long, value_enum, default_value = "bytes")]
+    length_unit: LengthUnit,
     /// Use a naive algorithm to limit password length
     ///
     /// Only affects results on passwords with limited max length.
@@ -44,13 +64,22 @@ struct Args {
     /// does not get longer than max length.
     #[arg(long)]
     naive: bool,
+    /// Never repeat the same word in the password
+    ///
+    /// With `--naive`, words are drawn without replacement. Otherwise (whether or not
+    /// `--max-length` is given), the weaker "no two adjacent words identical" guarantee is used
+    /// instead, since the exact algorithm can't efficiently track the full remaining word pool;
+    /// see [`word_otter::generate_words`]'s docs for why. Incompatible with `--mask`, `--template`,
+    /// `--index` and `--decode`.
+    #[arg(long, conflicts_with_all = ["mask", "template", "index", "decode"])]
+    distinct: bool,
     /// Don't lowercase the words in the resulting password
     #[arg(long, short = 'c')]
     keep_case: bool,
     /// Include words with umlauts
     #[arg(long, short = 'u')]
     use_umlauts: bool,
-    /// Only include words with a minimum length of this many bytes
+    /// Only include words with a minimum length of this many `--length-unit` units
     #[arg(long)]
     min_word_length: Option<usize>,
     /// Path to a list of words to assemble the password from
@@ -79,8 +108,81 @@ struct Args {
     /// Suppress all output except the password
     #[arg(long, short = 'q')]
     quiet: bool,
+    /// Use a mask/template instead of a plain word count, e.g. `?w-?w?d?d?s?w`
+    ///
+    /// `?w` draws a word from the loaded word list, `?d` a digit, `?l`/`?u` a lowercase/uppercase
+    /// ASCII letter, and `?s` a symbol from `--symbols`. Any other character is inserted
+    /// literally. `?w` tokens respect `--max-length`, splitting the remaining budget evenly
+    /// between them.
+    #[arg(long, conflicts_with_all = ["words", "template"])]
+    mask: Option<String>,
+    /// The symbol alphabet used for `?s` tokens in `--mask`
+    #[arg(long, default_value = "!@#$%^&*()-_=+")]
+    symbols: String,
+    /// Fill a `{category}` grammar template instead of a plain word count, e.g.
+    /// `{intensifier} {adjective} {noun}`
+    ///
+    /// Each `{category}` slot is filled with a word whose `category` (see the `.json` word list
+    /// format) matches the name in braces; text outside `{...}` is inserted literally. `--words`
+    /// is unused in this mode. `{category}` slots respect `--max-length`, splitting the remaining
+    /// budget evenly between them, the same way `--mask`'s `?w` tokens do.
+    #[arg(long, conflicts_with_all = ["words", "mask"])]
+    template: Option<String>,
+    /// Generate the passphrase at this index instead of sampling one at random
+    ///
+    /// Accepts a hex (`0x` prefix) or decimal integer in `[0, variations)`, where `variations` is
+    /// the same count reported as entropy. Given the same word list, `--words` and `--max-length`,
+    /// the same index always produces the same passphrase, which makes this useful for
+    /// seed-derived recovery (e.g. turning bytes from a BIP39-style mnemonic into a passphrase).
+    /// Requires `--max-length` and is incompatible with `--naive`, `--mask` and `--template`.
+    #[arg(long, value_parser = parse_index, conflicts_with_all = ["mask", "template"])]
+    index: Option<BigInteger>,
+    /// Print the index a previously generated passphrase corresponds to, instead of generating one
+    ///
+    /// This is the inverse of `--index`: pass the full passphrase (words and separators) and the
+    /// same `--words`/`--max-length` used to generate it. Requires `--max-length` and is
+    /// incompatible with `--naive`, `--mask` and `--template`.
+    #[arg(long, conflicts_with_all = ["mask", "template", "index"])]
+    decode: Option<String>,
+    /// Guarantee the password contains at least one uppercase letter
+    ///
+    /// Uppercases the first letter of the password. Since that letter would otherwise always be
+    /// lowercase, this is free: it doesn't add a real choice, so the reported entropy is
+    /// unaffected. Incompatible with `--keep-case`, `--mask`, `--template`, `--index` and
+    /// `--decode`.
+    #[arg(long, conflicts_with_all = ["keep_case", "mask", "template", "index", "decode"])]
+    require_upper: bool,
+    /// Guarantee the password contains at least one digit
+    ///
+    /// Already satisfied for free by the random digit separator between words. If `--sep-char` is
+    /// set, or there's only one word and thus no separator, a random digit is appended instead and
+    /// its 10 possibilities are folded into the reported entropy. Incompatible with `--mask`,
+    /// `--template`, `--index` and `--decode`.
+    #[arg(long, conflicts_with_all = ["mask", "template", "index", "decode"])]
+    require_digit: bool,
+    /// Guarantee the password contains at least one symbol from `--symbols`
+    ///
+    /// Appends a random symbol and folds its possibilities into the reported entropy.
+    /// Incompatible with `--mask`, `--template`, `--index` and `--decode`.
+    #[arg(long, conflicts_with_all = ["mask", "template", "index", "decode"])]
+    require_symbol: bool,
+    /// Estimate the entropy of an existing passphrase instead of generating one
+    ///
+    /// Segments `<passphrase>` against the loaded word list using greedy longest-match, then
+    /// reports how many word sequences of the same word count and total length the generator's
+    /// combinatorial model admits, the same way `--index`/`--decode` do. Word-list filtering
+    /// flags (`--keep-case`, `--use-umlauts`, `--min-word-length`, `--exclude`) still apply; any
+    /// segment that isn't a recognized word (e.g. a separator, or a typo) is flagged rather than
+    /// causing an error. Incompatible with `--mask`, `--template`, `--index`, `--decode`,
+    /// `--naive`, `--max-length` and `--words`.
+    #[arg(long, conflicts_with_all = [
+        "mask", "template", "index", "decode", "naive", "max_length", "words",
+        "require_upper", "require_digit", "require_symbol",
+    ])]
+    analyze: Option<String>,
     /// How many words to use
-    words: usize,
+    #[arg(required_unless_present_any = ["mask", "template", "analyze"])]
+    words: Option<usize>,
 }
 
 fn main() -> Result<()> {
@@ -88,36 +190,24 @@ fn main() -> Result<()> {
 
     let args = Args::parse();
 
-    // convert args
-    let words_count = args.words;
-    let separators_count = words_count.saturating_sub(1);
-    let separator_length = args.sep_char.map(|c| c.len_utf8()).unwrap_or(1);
-    let max_len_no_seps = args
-        .max_length
-        .map(|max_length| max_length.saturating_sub(separators_count * separator_length));
+    let length_unit: word_otter::LengthUnit = args.length_unit.into();
 
-    let words = read_wordlist(&args.word_list)?;
-
-    // short-circuit if they want an empty password
-    if words_count == 0 || max_len_no_seps == Some(0) {
-        println!("");
-        return Ok(());
-    }
+    let raw_words = read_wordlist(&args.word_list)?;
 
     let mut exclude_regexes = Vec::with_capacity(args.exclude.len());
 
-    for regex_string in args.exclude {
-        let mut builder = RegexBuilder::new(&regex_string);
+    for regex_string in &args.exclude {
+        let mut builder = RegexBuilder::new(regex_string);
         builder.case_insensitive(true);
         let regex = builder.build()?;
         exclude_regexes.push(regex);
     }
 
-    let words: Vec<RichWord> = words
+    let words: Vec<RichWord> = raw_words
         .into_iter()
         .filter(|word| {
             if let Some(min_word_length) = args.min_word_length {
-                word.word.len() >= min_word_length
+                length_unit.measure(&word.word) >= min_word_length
             } else {
                 true
             }
@@ -146,29 +236,230 @@ fn main() -> Result<()> {
         })
         .collect();
 
-    // generate words for passphrase
-    let mut password = String::new();
     let mut rng = rand::rngs::StdRng::from_entropy();
 
-    let (words, mut variations) = if args.naive || max_len_no_seps.is_none() {
-        generate_words_naive(&mut rng, words, words_count, max_len_no_seps)?
-    } else {
-        generate_words(&mut rng, words, words_count, max_len_no_seps.unwrap())?
-    };
+    if let Some(mask) = &args.mask {
+        let tokens = parse_mask(mask)?;
+        let (password, variations, min_entropy_bits) = generate_masked(
+            &mut rng,
+            words,
+            &tokens,
+            args.max_length,
+            &args.symbols,
+            length_unit,
+        )?;
+
+        println!("{}", password);
+
+        if !args.quiet {
+            if cfg!(debug_assertions) {
+                eprintln!("[Debug] Length: {}", password.len());
+                eprintln!("[Debug] Using {} for calculations", bigint::BIGINT_LIB);
+            }
 
-    // assemble password
-    for i in 0..words_count {
-        password.push_str(words[i].word.as_str());
+            let entropy =
+                bigint::RichEntropy::calculate_weighted(variations, min_entropy_bits as f32);
+            eprintln!(
+                "Entropy: {:.1} bits ({:.3}e{} possible variations)",
+                entropy.min_entropy_bits, entropy.variations_mantissa, entropy.variations_exponent
+            );
+        }
 
-        if i != words_count - 1 {
-            if let Some(sep_char) = args.sep_char {
-                password.push(sep_char);
-            } else {
-                let digit = rng.gen_range(0..=9);
-                password.push(char::from_digit(digit, 10).expect("digit is 0..=9"));
-                variations *= 10;
+        return Ok(());
+    }
+
+    if let Some(template) = &args.template {
+        let tokens = parse_template(template)?;
+        let (password, variations, min_entropy_bits) =
+            generate_templated(&mut rng, words, &tokens, args.max_length, length_unit)?;
+
+        println!("{}", password);
+
+        if !args.quiet {
+            if cfg!(debug_assertions) {
+                eprintln!("[Debug] Length: {}", password.len());
+                eprintln!("[Debug] Using {} for calculations", bigint::BIGINT_LIB);
+            }
+
+            let entropy =
+                bigint::RichEntropy::calculate_weighted(variations, min_entropy_bits as f32);
+            eprintln!(
+                "Entropy: {:.1} bits ({:.3}e{} possible variations)",
+                entropy.min_entropy_bits, entropy.variations_mantissa, entropy.variations_exponent
+            );
+        }
+
+        return Ok(());
+    }
+
+    if let Some(passphrase) = &args.analyze {
+        let mut normalized: String = passphrase.nfc().collect();
+        if !args.keep_case {
+            normalized = normalized.to_lowercase();
+        }
+
+        let word_db = WordDb::build_database(words, length_unit)
+            .ok_or_else(|| eyre!("Input file contained no valid words"))?;
+        let segments = segment_passphrase(&word_db, &normalized);
+        let variations = analyze_passphrase(word_db, &segments, length_unit);
+
+        if !args.quiet {
+            for segment in &segments {
+                match segment {
+                    Segment::Word(word) => eprintln!("  word:      {word}"),
+                    Segment::Unmatched(run) if run.chars().count() == 1 => {
+                        eprintln!("  separator: {run}")
+                    }
+                    Segment::Unmatched(run) => {
+                        eprintln!("  unknown:   {run} (not a recognized word)")
+                    }
+                }
+            }
+
+            if cfg!(debug_assertions) {
+                eprintln!("[Debug] Using {} for calculations", bigint::BIGINT_LIB);
+            }
+        }
+
+        let entropy = bigint::RichEntropy::calculate(variations);
+        println!(
+            "Entropy: {:.1} bits ({:.3}e{} possible variations)",
+            entropy.entropy_bits, entropy.variations_mantissa, entropy.variations_exponent
+        );
+
+        return Ok(());
+    }
+
+    // convert args
+    let words_count = args
+        .words
+        .expect("required unless --mask, --template or --analyze is given");
+    let separators_count = words_count.saturating_sub(1);
+    let separator_length = args
+        .sep_char
+        .map(|c| length_unit.measure(&c.to_string()))
+        .unwrap_or(1);
+    // `--require-digit`/`--require-symbol` each append one unit to the finished password (unless
+    // the digit is already covered for free by the `RandomDigit` separator, see their docs above);
+    // reserve that budget up front so `--max-length` still bounds the final, appended-to password.
+    let require_digit_appends =
+        args.require_digit && !(args.sep_char.is_none() && separators_count > 0);
+    let policy_reserved = require_digit_appends as usize + args.require_symbol as usize;
+    let max_len_no_seps = args.max_length.map(|max_length| {
+        max_length
+            .saturating_sub(separators_count * separator_length)
+            .saturating_sub(policy_reserved)
+    });
+
+    // short-circuit if they want an empty password
+    if words_count == 0 || max_len_no_seps == Some(0) {
+        println!("");
+        return Ok(());
+    }
+
+    if let Some(password) = &args.decode {
+        let max_length = max_len_no_seps.ok_or_else(|| eyre!("--decode requires --max-length"))?;
+        if args.naive {
+            bail!("--decode is incompatible with --naive");
+        }
+
+        let index = decode_password(
+            words,
+            words_count,
+            max_length,
+            password,
+            args.sep_char,
+            length_unit,
+        )?;
+        println!("{index}");
+        return Ok(());
+    }
+
+    if let Some(index) = args.index {
+        let max_length = max_len_no_seps.ok_or_else(|| eyre!("--index requires --max-length"))?;
+        if args.naive {
+            bail!("--index is incompatible with --naive");
+        }
+
+        let (password, words, variations) = generate_from_index(
+            words,
+            words_count,
+            max_length,
+            args.sep_char,
+            index,
+            length_unit,
+        )?;
+
+        println!("{}", password);
+
+        if !args.quiet {
+            if cfg!(debug_assertions) {
+                eprintln!("[Debug] Length: {}", password.len());
+                eprintln!("[Debug] Using {} for calculations", bigint::BIGINT_LIB);
+            }
+
+            let entropy = bigint::RichEntropy::calculate(variations);
+            eprintln!(
+                "Entropy: {:.1} bits ({:.3}e{} possible variations)",
+                entropy.entropy_bits, entropy.variations_mantissa, entropy.variations_exponent
+            );
+
+            if !args.no_meanings {
+                for word in words {
+                    if !word.meanings.is_empty() {
+                        eprintln!("Meanings for \"{}\":", word.word);
+                        for meaning in word.meanings {
+                            eprintln!("  - {}", meaning);
+                        }
+                    }
+                }
             }
         }
+
+        return Ok(());
+    }
+
+    // generate words for passphrase
+    let word_db = WordDb::build_database(words, length_unit)
+        .ok_or_else(|| eyre!("Input file contained no valid words"))?;
+
+    let mut builder = PassphraseBuilder::new(words_count)
+        .naive(args.naive)
+        .distinct(args.distinct);
+    if let Some(max_length) = max_len_no_seps {
+        builder = builder.max_length(max_length);
+    }
+    if let Some(sep_char) = args.sep_char {
+        builder = builder.separator(Separator::Fixed(sep_char));
+    }
+
+    let GeneratedPassphrase {
+        mut password,
+        words,
+        mut variations,
+        mut min_entropy_bits,
+    } = builder.generate(&mut rng, word_db)?;
+
+    // enforce character-class policy flags
+    if args.require_upper {
+        password = force_first_upper(&password);
+    }
+
+    if require_digit_appends {
+        let digit = rng.gen_range(0..=9);
+        password.push(char::from_digit(digit, 10).expect("digit is 0..=9"));
+        variations *= 10;
+        min_entropy_bits += 10.0_f64.log2();
+    }
+
+    if args.require_symbol {
+        let symbols: Vec<char> = args.symbols.chars().collect();
+        if symbols.is_empty() {
+            bail!("--symbols must not be empty when --require-symbol is set");
+        }
+        password.push(symbols[rng.gen_range(0..symbols.len())]);
+        variations *= symbols.len();
+        min_entropy_bits += (symbols.len() as f64).log2();
     }
 
     println!("{}", password);
@@ -181,10 +472,10 @@ fn main() -> Result<()> {
         }
 
         // print entropy
-        let entropy = bigint::RichEntropy::calculate(variations);
+        let entropy = bigint::RichEntropy::calculate_weighted(variations, min_entropy_bits as f32);
         eprintln!(
             "Entropy: {:.1} bits ({:.3}e{} possible variations)",
-            entropy.entropy_bits, entropy.variations_mantissa, entropy.variations_exponent
+            entropy.min_entropy_bits, entropy.variations_mantissa, entropy.variations_exponent
         );
 
         // print meanings
@@ -266,394 +557,542 @@ fn parse_txt_wordlist(reader: impl BufRead) -> Result<Vec<RichWord>> {
         words.push(RichWord {
             word: word?,
             meanings: Vec::new(),
+            category: None,
+            weight: None,
         });
     }
 
     Ok(words)
 }
 
-#[derive(Debug, Default, Clone, serde::Deserialize)]
-struct RichWord {
-    word: String,
-    #[serde(default)]
-    meanings: Vec<String>,
-}
-
 fn parse_json_wordlist(reader: impl BufRead) -> Result<Vec<RichWord>> {
     let words: Vec<RichWord> = serde_json::from_reader(reader)?;
     Ok(words)
 }
 
-#[derive(Debug)]
-struct WordDb {
-    word_groups: HashMap<NonZeroUsize, Vec<String>>,
-    min_length: NonZeroUsize,
-    meanings: HashMap<String, Vec<String>>,
+/// Parses a `--index` value given in hex (`0x` prefix) or decimal.
+fn parse_index(input: &str) -> Result<BigInteger> {
+    let (digits, radix) = match input.strip_prefix("0x").or_else(|| input.strip_prefix("0X")) {
+        Some(hex) => (hex, 16),
+        None => (input, 10),
+    };
+
+    BigInteger::from_str_radix(digits, radix)
+        .map_err(|_| eyre!("Invalid --index value '{input}'"))
 }
 
-impl WordDb {
-    ///
-    /// Returns None if words is empty or only contains empty strings.
-    ///
-    fn build_database(mut words: Vec<RichWord>) -> Option<Self> {
-        // run unicode normalization on all words
-        words = words
-            .into_iter()
-            .map(|RichWord { word, meanings }| RichWord {
-                word: word.nfc().collect(),
-                meanings,
-            })
-            .collect();
-        // sort words alphabetically
-        words.sort_unstable_by(|a, b| a.word.cmp(&b.word));
-        // merge duplicates
-        words = words
-            .into_iter()
-            .coalesce(|mut a, b| {
-                if a.word == b.word {
-                    a.meanings.extend(b.meanings);
-                    Ok(a)
-                } else {
-                    Err((a, b))
-                }
-            })
-            .collect();
-        // remove 0-length strings
-        if words
-            .first()
-            .map(|word| word.word.is_empty())
-            .unwrap_or(false)
-        {
-            words.remove(0);
+/// Uppercases the first alphabetic character in `password`, leaving everything else untouched.
+///
+/// Used by `--require-upper`; see its help text for why this doesn't affect the reported entropy.
+fn force_first_upper(password: &str) -> String {
+    let mut out = String::with_capacity(password.len());
+    let mut uppercased = false;
+
+    for c in password.chars() {
+        if !uppercased && c.is_alphabetic() {
+            out.extend(c.to_uppercase());
+            uppercased = true;
+        } else {
+            out.push(c);
         }
+    }
+
+    out
+}
+
+/// The deterministic counterpart of [`word_otter::generate_words`]/the main generation loop:
+/// turns an `--index` into the exact passphrase it corresponds to.
+///
+/// The index is split into a word-choice part and a separator-digit part (when using random
+/// digit separators, i.e. `sep_char` is `None`), mirroring the order separators are appended to
+/// the password in `main`: the word part occupies the high-order digits, the `words - 1`
+/// separator digits the low-order ones, one decimal digit per separator.
+/// [`word_otter::generate_words_from_index`] then turns the word part back into a word sequence.
+fn generate_from_index(
+    input_words: Vec<RichWord>,
+    words: usize,
+    max_length: usize,
+    sep_char: Option<char>,
+    index: BigInteger,
+    length_unit: word_otter::LengthUnit,
+) -> Result<(String, Vec<RichWord>, BigInteger)> {
+    let word_db = match WordDb::build_database(input_words, length_unit) {
+        None => bail!("Input file contained no valid words"),
+        Some(word_db) => word_db,
+    };
 
-        if words.is_empty() {
-            return None;
+    let separators_count = words.saturating_sub(1);
+    let mut divisor = BigInteger::from(1);
+    if sep_char.is_none() {
+        for _ in 0..separators_count {
+            divisor *= 10;
         }
+    }
 
-        let mut map = HashMap::new();
-        let mut meanings = HashMap::new();
-        let mut min_length: NonZeroUsize = words[0].word.len().try_into().expect("no empty words");
-        let mut max_length: NonZeroUsize = min_length;
+    let word_index = index.clone() / divisor.clone();
+    let sep_part = index % divisor.clone();
+
+    let (generated_words, word_variations) =
+        word_otter::generate_words_from_index(word_db, words, max_length, word_index)?;
+    let variations = word_variations * divisor;
+
+    let sep_digits: Vec<char> = if sep_char.is_none() {
+        format!(
+            "{:0>width$}",
+            sep_part.to_string(),
+            width = separators_count
+        )
+        .chars()
+        .collect()
+    } else {
+        Vec::new()
+    };
 
-        for RichWord {
-            word,
-            meanings: word_meanings,
-        } in words
-        {
-            let length = word.len().try_into().expect("no empty words");
+    let mut password = String::new();
+    for (i, word) in generated_words.iter().enumerate() {
+        password.push_str(&word.word);
+        if i != separators_count {
+            match sep_char {
+                Some(c) => password.push(c),
+                None => password.push(sep_digits[i]),
+            }
+        }
+    }
 
-            let group_vec = map.entry(length).or_insert(Vec::new());
-            group_vec.push(word.clone());
+    Ok((password, generated_words, variations))
+}
 
-            meanings
-                .entry(word)
-                .and_modify(|vec: &mut Vec<String>| vec.extend_from_slice(&word_meanings))
-                .or_insert(word_meanings);
+/// The inverse of [`generate_from_index`]: turns a previously generated passphrase back into the
+/// `--index` that produced it, using [`word_otter::index_of_words`].
+///
+/// Splits `password` back into its words and (when using random digit separators) its separator
+/// digits by character class, since word lists contain no digits: a run of non-digit characters
+/// is a word, a single digit is a separator. With a fixed `sep_char` the password is split on that
+/// character instead.
+fn decode_password(
+    input_words: Vec<RichWord>,
+    words: usize,
+    max_length: usize,
+    password: &str,
+    sep_char: Option<char>,
+    length_unit: word_otter::LengthUnit,
+) -> Result<BigInteger> {
+    let word_db = match WordDb::build_database(input_words, length_unit) {
+        None => bail!("Input file contained no valid words"),
+        Some(word_db) => word_db,
+    };
 
-            if length > max_length {
-                max_length = length;
-            }
-            if length < min_length {
-                min_length = length;
+    let (chosen_words, sep_digits) = match sep_char {
+        Some(c) => {
+            let parts: Vec<String> = password.split(c).map(String::from).collect();
+            if parts.len() != words {
+                bail!(
+                    "Password splits into {} words on '{c}', expected {words}",
+                    parts.len()
+                );
             }
+            (parts, String::new())
         }
+        None => split_digit_separated_words(password, words)?,
+    };
 
-        for group_len in 1..max_length.get() {
-            let group_len = NonZeroUsize::new(group_len).unwrap();
+    let word_rank = word_otter::index_of_words(word_db, max_length, &chosen_words);
 
-            let _ignored = map.entry(group_len).or_insert(Vec::new());
+    let separators_count = words.saturating_sub(1);
+    let mut divisor = BigInteger::from(1);
+    if sep_char.is_none() {
+        for _ in 0..separators_count {
+            divisor *= 10;
         }
-
-        Some(WordDb {
-            word_groups: map,
-            min_length,
-            meanings,
-        })
     }
 
-    fn get_group(&self, len: NonZeroUsize) -> &Vec<String> {
-        self.word_groups.get(&len).unwrap()
-    }
+    let sep_part = if sep_digits.is_empty() {
+        BigInteger::ZERO
+    } else {
+        BigInteger::from_str_radix(&sep_digits, 10)
+            .map_err(|_| eyre!("Password contains a non-digit separator"))?
+    };
 
-    ///
-    /// n_len: Returns the number of words with the given length.
-    ///
-    fn group_size(&self, len: NonZeroUsize) -> usize {
-        if let Some(group_vec) = self.word_groups.get(&len) {
-            group_vec.len()
+    Ok(word_rank * divisor + sep_part)
+}
+
+/// Splits a passphrase that uses random digit separators back into its words and separator
+/// digits, relying on word lists never containing digit characters.
+fn split_digit_separated_words(password: &str, words: usize) -> Result<(Vec<String>, String)> {
+    let mut chosen_words = Vec::with_capacity(words);
+    let mut digits = String::new();
+    let mut current = String::new();
+
+    for c in password.chars() {
+        if c.is_ascii_digit() {
+            chosen_words.push(std::mem::take(&mut current));
+            digits.push(c);
         } else {
-            0
+            current.push(c);
         }
     }
+    chosen_words.push(current);
 
-    fn shortest_group_len(&self) -> NonZeroUsize {
-        self.min_length
+    if chosen_words.len() != words {
+        bail!(
+            "Password splits into {} words, expected {words}",
+            chosen_words.len()
+        );
     }
 
-    fn attach_meanings(&self, words: &[String]) -> Vec<RichWord> {
-        words
-            .iter()
-            .map(|word| RichWord {
-                word: word.clone(),
-                meanings: self.meanings.get(word).cloned().unwrap_or_default(),
-            })
-            .collect()
-    }
+    Ok((chosen_words, digits))
 }
 
-struct Algorithm {
-    word_db: WordDb,
-    memoize_variations_for_length: HashMap<u32, BigInteger>,
-    memoize_unreachable_variations_at_depth: HashMap<(u32, u32), BigInteger>,
+/// A single segment of a passphrase, as produced by [`segment_passphrase`] for `--analyze`.
+enum Segment {
+    /// A substring found verbatim in the loaded word list.
+    Word(String),
+    /// A run of characters that matched no word in the list. Usually a separator (a digit, or a
+    /// fixed `--sep-char`), but could also be a typo or a word missing from the list.
+    Unmatched(String),
 }
 
-#[allow(non_snake_case)]
-impl Algorithm {
-    fn new(word_db: WordDb) -> Self {
-        Algorithm {
-            word_db,
-            memoize_variations_for_length: Default::default(),
-            memoize_unreachable_variations_at_depth: Default::default(),
+/// Greedily segments `passphrase` against `word_db` for `--analyze`, always preferring the
+/// longest substring starting at the current position that matches a known word, and falling
+/// back to a single unmatched character when nothing does.
+///
+/// This is the same greedy longest-match approach cracken uses for hybrid-mask entropy
+/// estimation. It isn't guaranteed to find the segmentation the passphrase was actually generated
+/// with (a word could itself contain a shorter word as a substring), but for a passphrase drawn
+/// from this word list it reliably finds *a* valid one, word separators included.
+fn segment_passphrase(word_db: &WordDb, passphrase: &str) -> Vec<Segment> {
+    let chars: Vec<char> = passphrase.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let longest_match = (i + 1..=chars.len())
+            .rev()
+            .map(|end| chars[i..end].iter().collect::<String>())
+            .find(|candidate| word_db.contains_word(candidate));
+
+        match longest_match {
+            Some(word) => {
+                i += word.chars().count();
+                segments.push(Segment::Word(word));
+            }
+            None => {
+                if let Some(Segment::Unmatched(run)) = segments.last_mut() {
+                    run.push(chars[i]);
+                } else {
+                    segments.push(Segment::Unmatched(chars[i].to_string()));
+                }
+                i += 1;
+            }
         }
     }
 
-    fn variations_for_length(&mut self, max_length: u32) -> &BigInteger {
-        fn variations_for_length_impl(
-            word_db: &WordDb,
-            memoization: &HashMap<u32, BigInteger>,
-            max_length: u32,
-        ) -> BigInteger {
-            if max_length <= 0 {
-                BigInteger::from(1)
-            } else {
-                let mut sum = BigInteger::ZERO;
-
-                for group_len in 1..=max_length {
-                    let n_k = word_db.group_size(
-                        NonZeroUsize::new(group_len.try_into().expect("iterator over range 1.."))
-                            .expect("iterator over range 1.."),
-                    );
-
-                    let f_x_minus_k = memoization
-                        .get(&(max_length - group_len))
-                        .expect("must have been calculated before");
-
-                    sum += n_k * f_x_minus_k;
-                }
+    segments
+}
 
-                sum
-            }
-        }
+/// Estimates the entropy of a passphrase already segmented by [`segment_passphrase`], using the
+/// same combinatorial model as generation: the word segments' count and total length (measured in
+/// `length_unit`) are fed to [`Algorithm::variations_for_length_and_depth`], the same way
+/// `--index`/`--decode` use `--words`/`--max-length`.
+///
+/// A lone digit between words is assumed to be a [`Separator::RandomDigit`] and folds its 10
+/// possibilities into the result, mirroring generation. Any other unmatched segment is assumed to
+/// be a fixed, zero-entropy separator (a `--sep-char`) if it's a single character; longer
+/// unmatched runs contribute no variations, since there's no way to know how they were chosen.
+fn analyze_passphrase(
+    word_db: WordDb,
+    segments: &[Segment],
+    length_unit: word_otter::LengthUnit,
+) -> BigInteger {
+    let total_length: u32 = segments
+        .iter()
+        .filter_map(|segment| match segment {
+            Segment::Word(word) => Some(u32::try_from(length_unit.measure(word)).unwrap()),
+            Segment::Unmatched(_) => None,
+        })
+        .sum();
+    let word_count = u32::try_from(
+        segments
+            .iter()
+            .filter(|segment| matches!(segment, Segment::Word(_)))
+            .count(),
+    )
+    .unwrap();
 
-        let memoization = &mut self.memoize_variations_for_length;
+    let mut variations = if word_count == 0 {
+        BigInteger::from(1)
+    } else {
+        Algorithm::new(word_db).variations_for_length_and_depth(total_length, word_count)
+    };
 
-        if !memoization.contains_key(&max_length) {
-            // begin calculating values from the bottom up
-            for max_length_ in 0..=max_length {
-                if !memoization.contains_key(&(max_length_)) {
-                    let value =
-                        variations_for_length_impl(&self.word_db, &memoization, max_length_);
-                    memoization.insert(max_length_, value);
+    for segment in segments {
+        if let Segment::Unmatched(run) = segment {
+            if let [c] = run.chars().collect::<Vec<_>>()[..] {
+                if c.is_ascii_digit() {
+                    variations *= 10;
                 }
             }
         }
+    }
 
-        memoization
-            .get(&max_length)
-            .expect("has just been calculated if it didn't exist")
-    }
-
-    fn unreachable_variations_at_depth(&mut self, max_length: u32, depth: u32) -> &BigInteger {
-        fn unreachable_variations_at_depth_impl(
-            word_db: &WordDb,
-            memoization: &HashMap<(u32, u32), BigInteger>,
-            memoization_variations: &HashMap<u32, BigInteger>,
-            max_length: u32,
-            depth: u32,
-        ) -> BigInteger {
-            if depth == 0 {
-                let f_x = memoization_variations
-                    .get(&(max_length))
-                    .expect("must have been calculated before");
-
-                f_x - BigInteger::from(1)
-            } else {
-                let mut sum = BigInteger::ZERO;
-
-                for group_len in 1..=max_length {
-                    let n_k = word_db.group_size(
-                        NonZeroUsize::new(group_len.try_into().expect("iterator over range 1.."))
-                            .expect("iterator over range 1.."),
-                    );
-
-                    let g_x_minus_k_minus_one_D_minus_one = memoization
-                        .get(&(max_length - (group_len - 1), depth - 1))
-                        .expect("must have been calculated before");
+    variations
+}
 
-                    sum += n_k * g_x_minus_k_minus_one_D_minus_one;
-                }
+/// A single token of a `--mask` template.
+///
+/// See [`parse_mask`] for the supported syntax.
+#[derive(Debug, Clone, Copy)]
+enum MaskToken {
+    /// `?w`: a word drawn from the loaded word list
+    Word,
+    /// `?d`: a digit `0-9`
+    Digit,
+    /// `?l`: a lowercase ASCII letter
+    Lower,
+    /// `?u`: an uppercase ASCII letter
+    Upper,
+    /// `?s`: a symbol from `--symbols`
+    Symbol,
+    /// Any other character, inserted verbatim
+    Literal(char),
+}
 
-                sum
-            }
-        }
+/// Parses a cracken-style mask string into a sequence of [`MaskToken`]s.
+fn parse_mask(mask: &str) -> Result<Vec<MaskToken>> {
+    let mut tokens = Vec::new();
+    let mut chars = mask.chars();
 
-        // prime required values
-        self.variations_for_length(max_length);
-
-        let memoization = &mut self.memoize_unreachable_variations_at_depth;
-
-        if !memoization.contains_key(&(max_length, depth)) {
-            // begin calculating values from the bottom up
-            for depth_ in 0..=depth {
-                for max_length_ in 0..=max_length {
-                    if !memoization.contains_key(&(max_length_, depth_)) {
-                        let value = unreachable_variations_at_depth_impl(
-                            &self.word_db,
-                            &memoization,
-                            &self.memoize_variations_for_length,
-                            max_length_,
-                            depth_,
-                        );
-                        memoization.insert((max_length_, depth_), value);
-                    }
-                }
-            }
+    while let Some(c) = chars.next() {
+        if c != '?' {
+            tokens.push(MaskToken::Literal(c));
+            continue;
         }
 
-        memoization
-            .get(&(max_length, depth))
-            .expect("has just been calculated if it didn't exist")
+        tokens.push(match chars.next() {
+            Some('w') => MaskToken::Word,
+            Some('d') => MaskToken::Digit,
+            Some('l') => MaskToken::Lower,
+            Some('u') => MaskToken::Upper,
+            Some('s') => MaskToken::Symbol,
+            Some(other) => bail!("Unknown mask token '?{other}'"),
+            None => bail!("Mask ends with a dangling '?'"),
+        });
     }
 
-    ///
-    /// Returns the number of possible variations chaining this number of `words` up to a `max_length`.
-    ///
-    fn variations_for_length_and_depth(&mut self, max_length: u32, depth: u32) -> BigInteger {
-        let f_x = self.variations_for_length(max_length).clone();
-        let g_x_minus_D_D =
-            self.unreachable_variations_at_depth(max_length.saturating_sub(depth), depth);
-
-        f_x - g_x_minus_D_D
-    }
+    Ok(tokens)
 }
 
-#[allow(non_snake_case)]
-fn generate_words(
-    rng: &mut impl Rng,
+/// Generates a passphrase from a parsed `--mask` template, mixing word and character-class
+/// tokens.
+///
+/// `?w` tokens dispatch into [`word_otter::generate_words`]/[`word_otter::generate_words_naive`],
+/// splitting the `max_length` budget evenly between them (after reserving one unit per
+/// character-class or literal token). The returned `variations` is the product of every token's
+/// choice count; the returned `min_entropy_bits` is the sum of every token's worst-case entropy
+/// (equal to `log2(variations)` unless a `?w` token drew from a weighted word list), so pass both
+/// to [`bigint::RichEntropy::calculate_weighted`] for an honest report.
+fn generate_masked(
+    rng: &mut (impl Rng + rand::CryptoRng),
     input_words: Vec<RichWord>,
-    words: usize,
-    max_length: usize,
-) -> Result<(Vec<RichWord>, BigInteger)> {
-    let word_db = match WordDb::build_database(input_words) {
-        None => bail!("Input file contained no valid words"),
-        Some(word_db) => word_db,
-    };
-
-    if words * word_db.shortest_group_len().get() > max_length {
-        bail!("Length constraints cannot be fulfilled");
+    mask: &[MaskToken],
+    max_length: Option<usize>,
+    symbols: &str,
+    length_unit: word_otter::LengthUnit,
+) -> Result<(String, BigInteger, f64)> {
+    let symbols: Vec<char> = symbols.chars().collect();
+    if symbols.is_empty() && mask.iter().any(|token| matches!(token, MaskToken::Symbol)) {
+        bail!("--symbols must not be empty when the mask contains a '?s' token");
     }
 
-    let mut generated_words: Vec<String> = Vec::with_capacity(words);
-    let mut algorithm = Algorithm::new(word_db);
+    let word_token_count = mask
+        .iter()
+        .filter(|token| matches!(token, MaskToken::Word))
+        .count();
 
-    // TODO unwrap
-    let mut max_length = u32::try_from(max_length).unwrap();
-    let mut words = u32::try_from(words).unwrap();
+    let per_word_max_length = max_length.map(|max_length| {
+        let reserved: usize = mask.len() - word_token_count;
+        max_length.saturating_sub(reserved) / word_token_count.max(1)
+    });
 
-    // already calculates and memoizes all values used in the following loop
-    let variations = algorithm.variations_for_length_and_depth(max_length, words);
+    let mut password = String::new();
+    let mut variations = BigInteger::from(1);
+    let mut min_entropy_bits = 0.0_f64;
 
-    while words > 0 {
-        let step_max_len: u32 = max_length - (words - 1);
+    for token in mask {
+        match token {
+            MaskToken::Literal(c) => password.push(*c),
+            MaskToken::Digit => {
+                let digit = rng.gen_range(0..=9);
+                password.push(char::from_digit(digit, 10).expect("digit is 0..=9"));
+                variations *= 10;
+                min_entropy_bits += 10.0_f64.log2();
+            }
+            MaskToken::Lower => {
+                password.push((b'a' + rng.gen_range(0..26)) as char);
+                variations *= 26;
+                min_entropy_bits += 26.0_f64.log2();
+            }
+            MaskToken::Upper => {
+                password.push((b'A' + rng.gen_range(0..26)) as char);
+                variations *= 26;
+                min_entropy_bits += 26.0_f64.log2();
+            }
+            MaskToken::Symbol => {
+                password.push(symbols[rng.gen_range(0..symbols.len())]);
+                variations *= symbols.len();
+                min_entropy_bits += (symbols.len() as f64).log2();
+            }
+            MaskToken::Word => {
+                let word_db = WordDb::build_database(input_words.clone(), length_unit)
+                    .ok_or_else(|| eyre!("Input file contained no valid words"))?;
+                let word_min_entropy_bits;
+                let (mut word, word_variations) = match per_word_max_length {
+                    Some(max_length) => {
+                        let (word, word_variations, min_entropy) =
+                            word_otter::generate_words(rng, word_db, 1, max_length, false)?;
+                        word_min_entropy_bits = min_entropy;
+                        (word, word_variations)
+                    }
+                    None => {
+                        let (word, word_variations) =
+                            word_otter::generate_words_naive(rng, word_db, 1, None, false)?;
+                        // naive mode ignores RichWord::weight, so selection is uniform and the
+                        // worst case equals the average case
+                        word_min_entropy_bits =
+                            bigint::RichEntropy::calculate(word_variations.clone()).entropy_bits
+                                as f64;
+                        (word, word_variations)
+                    }
+                };
+                password.push_str(&word.remove(0).word);
+                variations *= word_variations;
+                min_entropy_bits += word_min_entropy_bits;
+            }
+        }
+    }
 
-        let distr_iter = (1..=step_max_len).map(|group_len| {
-            let n_k = algorithm.word_db.group_size(
-                NonZeroUsize::new(group_len.try_into().unwrap()).expect("iterator over range 1.."),
-            );
-            let f_dash_x_minus_k_D_minus_one =
-                algorithm.variations_for_length_and_depth(step_max_len - group_len, words - 1);
+    Ok((password, variations, min_entropy_bits))
+}
 
-            IntegerWrapper(n_k * f_dash_x_minus_k_D_minus_one)
-        });
-        let distribution = WeightedIndex::new(distr_iter).unwrap();
+/// A single token of a `--template` grammar, as parsed by [`parse_template`].
+#[derive(Debug, Clone)]
+enum TemplateToken {
+    /// `{category}`: a word drawn from the loaded word list whose `category` matches
+    Slot(String),
+    /// Any run of text outside `{...}`, inserted verbatim
+    Literal(String),
+}
+
+/// Parses a `--template` grammar string, e.g. `{intensifier} {adjective} {noun}`, into a sequence
+/// of [`TemplateToken`]s.
+fn parse_template(template: &str) -> Result<Vec<TemplateToken>> {
+    let mut tokens = Vec::new();
+    let mut chars = template.chars();
+    let mut literal = String::new();
+
+    while let Some(c) = chars.next() {
+        if c == '{' {
+            if !literal.is_empty() {
+                tokens.push(TemplateToken::Literal(std::mem::take(&mut literal)));
+            }
 
-        let group_len = 1 + rng.sample(&distribution);
-        let group = algorithm
-            .word_db
-            .get_group(NonZeroUsize::new(group_len).unwrap());
-        let index = rng.gen_range(0..group.len());
-        let word = group[index].clone();
+            let category: String = chars.by_ref().take_while(|&c| c != '}').collect();
+            if category.is_empty() {
+                bail!("Template contains an empty '{{}}' slot");
+            }
+            tokens.push(TemplateToken::Slot(category));
+        } else if c == '}' {
+            bail!("Template contains a '}}' without a matching '{{'");
+        } else {
+            literal.push(c);
+        }
+    }
 
-        max_length -= u32::try_from(word.len()).unwrap();
-        words -= 1;
-        generated_words.push(word);
+    if !literal.is_empty() {
+        tokens.push(TemplateToken::Literal(literal));
     }
 
-    Ok((
-        algorithm.word_db.attach_meanings(&generated_words),
-        variations,
-    ))
+    Ok(tokens)
 }
 
-fn generate_words_naive(
-    rng: &mut impl Rng,
-    mut input_words: Vec<RichWord>,
-    words: usize,
+/// Generates a passphrase from a parsed `--template` grammar, filling each `{category}` slot with
+/// a word whose `category` matches, and inserting literal text verbatim.
+///
+/// Like [`generate_masked`]'s `?w` tokens, each slot dispatches into
+/// [`word_otter::generate_words`]/[`word_otter::generate_words_naive`], splitting the
+/// `max_length` budget evenly between slots (after reserving the literal text's own length,
+/// measured in `length_unit`). The
+/// returned `variations` is the product of every slot's choice count; the returned
+/// `min_entropy_bits` is the sum of every slot's worst-case entropy (equal to `log2(variations)`
+/// unless a slot drew from a weighted word list), so pass both to
+/// [`bigint::RichEntropy::calculate_weighted`] for an honest report.
+fn generate_templated(
+    rng: &mut (impl Rng + rand::CryptoRng),
+    input_words: Vec<RichWord>,
+    template: &[TemplateToken],
     max_length: Option<usize>,
-) -> Result<(Vec<RichWord>, BigInteger)> {
-    let max_word_length = max_length.map(|len| len / words);
+    length_unit: word_otter::LengthUnit,
+) -> Result<(String, BigInteger, f64)> {
+    let slot_count = template
+        .iter()
+        .filter(|token| matches!(token, TemplateToken::Slot(_)))
+        .count();
+    if slot_count == 0 {
+        bail!("Template contains no '{{category}}' slots");
+    }
 
-    // run unicode normalization on all words and filter max length
-    input_words = input_words
-        .into_iter()
-        .filter(|word| {
-            if let Some(max_len) = max_word_length {
-                word.word.len() <= max_len
-            } else {
-                true
-            }
+    let literal_len: usize = template
+        .iter()
+        .map(|token| match token {
+            TemplateToken::Literal(s) => length_unit.measure(s),
+            TemplateToken::Slot(_) => 0,
         })
-        .map(|RichWord { word, meanings }| RichWord {
-            word: word.nfc().collect(),
-            meanings,
-        })
-        .collect();
-    // sort words alphabetically
-    input_words.sort_unstable_by(|a, b| a.word.cmp(&b.word));
-    // merge duplicates
-    input_words = input_words
-        .into_iter()
-        .coalesce(|mut a, b| {
-            if a.word == b.word {
-                a.meanings.extend(b.meanings);
-                Ok(a)
-            } else {
-                Err((a, b))
-            }
-        })
-        .collect();
-    // remove 0-length strings
-    if input_words
-        .first()
-        .map(|word| word.word.is_empty())
-        .unwrap_or(false)
-    {
-        input_words.remove(0);
-    }
+        .sum();
 
-    if input_words.is_empty() {
-        bail!("Input file contained no valid words");
-    }
+    let per_word_max_length =
+        max_length.map(|max_length| max_length.saturating_sub(literal_len) / slot_count);
 
-    let mut out_words = Vec::with_capacity(words);
+    let mut password = String::new();
     let mut variations = BigInteger::from(1);
-
-    for _ in 0..words {
-        let word_index = rng.gen_range(0..input_words.len());
-        out_words.push(input_words[word_index].clone());
-        variations *= input_words.len();
+    let mut min_entropy_bits = 0.0_f64;
+
+    for token in template {
+        match token {
+            TemplateToken::Literal(s) => password.push_str(s),
+            TemplateToken::Slot(category) => {
+                let candidates: Vec<RichWord> = input_words
+                    .iter()
+                    .filter(|word| word.category.as_deref() == Some(category.as_str()))
+                    .cloned()
+                    .collect();
+                let word_db = WordDb::build_database(candidates, length_unit).ok_or_else(|| {
+                    eyre!("No words tagged with category '{category}' in the input file")
+                })?;
+                let word_min_entropy_bits;
+                let (mut word, word_variations) = match per_word_max_length {
+                    Some(max_length) => {
+                        let (word, word_variations, min_entropy) =
+                            word_otter::generate_words(rng, word_db, 1, max_length, false)?;
+                        word_min_entropy_bits = min_entropy;
+                        (word, word_variations)
+                    }
+                    None => {
+                        let (word, word_variations) =
+                            word_otter::generate_words_naive(rng, word_db, 1, None, false)?;
+                        // naive mode ignores RichWord::weight, so selection is uniform and the
+                        // worst case equals the average case
+                        word_min_entropy_bits =
+                            bigint::RichEntropy::calculate(word_variations.clone()).entropy_bits
+                                as f64;
+                        (word, word_variations)
+                    }
+                };
+                password.push_str(&word.remove(0).word);
+                variations *= word_variations;
+                min_entropy_bits += word_min_entropy_bits;
+            }
+        }
     }
 
-    Ok((out_words, variations))
+    Ok((password, variations, min_entropy_bits))
 }